@@ -0,0 +1,130 @@
+use git2::Repository;
+use std::collections::HashMap;
+use std::net::ToSocketAddrs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use crate::suggest::{Priority, Suggestion};
+
+/// Whether each remote host resolved, so a workspace full of clones from the
+/// same server costs one DNS lookup.
+static RESOLVED_HOSTS: LazyLock<Mutex<HashMap<String, bool>>> = LazyLock::new(Default::default);
+
+static THOROUGH: AtomicBool = AtomicBool::new(false);
+
+/// Also run the slow and opinionated checks (`--audit`): a DNS lookup per
+/// remote host, and settings that are a matter of taste such as `pull.rebase`.
+pub fn set_thorough(thorough: bool) {
+    THOROUGH.store(thorough, Ordering::Relaxed);
+}
+
+fn thorough() -> bool {
+    THOROUGH.load(Ordering::Relaxed)
+}
+
+/// Check the repository's effective git config (local, global and system
+/// merged) for settings that commonly cause trouble, each with the
+/// `git config` command that fixes it. The checks behind `--audit` only run
+/// when it is set.
+pub fn config_suggestions(repo_path: &Path) -> Vec<Suggestion> {
+    let Ok(repo) = Repository::open(repo_path) else {
+        return Vec::new();
+    };
+    let Ok(config) = repo.config() else {
+        return Vec::new();
+    };
+    let get = |key: &str| config.get_string(key).ok().filter(|v| !v.trim().is_empty());
+
+    let mut suggestions = Vec::new();
+
+    if get("user.email").is_none() {
+        suggestions.push(Suggestion::new(
+            Priority::High,
+            "user.email is not set; commits will be attributed to a guessed address",
+            Some("git config user.email \"you@example.com\""),
+        ));
+    }
+
+    if let Some(autocrlf) = get("core.autocrlf") {
+        let autocrlf = autocrlf.to_lowercase();
+        if cfg!(windows) && autocrlf == "input" {
+            suggestions.push(Suggestion::new(
+                Priority::Medium,
+                "core.autocrlf is `input` on Windows; checked-out files keep LF endings",
+                Some("git config core.autocrlf true"),
+            ));
+        } else if !cfg!(windows) && autocrlf == "true" {
+            suggestions.push(Suggestion::new(
+                Priority::Medium,
+                "core.autocrlf is `true` on a non-Windows system; files are checked out with CRLF endings",
+                Some("git config core.autocrlf input"),
+            ));
+        }
+    }
+
+    if !thorough() {
+        return suggestions;
+    }
+
+    if get("pull.rebase").is_none() {
+        suggestions.push(Suggestion::new(
+            Priority::Low,
+            "pull.rebase is not set; pulling diverged branches fails until you choose merge or rebase",
+            Some("git config pull.rebase false"),
+        ));
+    }
+
+    if !crate::offline::enabled()
+        && let Some(url) = get("remote.origin.url")
+        && let Some(host) = remote_host(&url)
+        && !resolves(&host)
+    {
+        suggestions.push(Suggestion::new(
+            Priority::Medium,
+            format!("origin points at {}, which does not resolve (moved server, or offline?)", host),
+            Some("git remote set-url origin <new-url>"),
+        ));
+    }
+
+    suggestions
+}
+
+/// Host part of a remote URL: `https://host/...`, `ssh://user@host:port/...`
+/// or scp-like `user@host:path`. Local paths and `file://` URLs have none.
+fn remote_host(url: &str) -> Option<String> {
+    let authority = if let Some((scheme, rest)) = url.split_once("://") {
+        if scheme == "file" {
+            return None;
+        }
+        rest.split('/').next()?
+    } else {
+        // scp-like syntax needs a colon before any slash; otherwise it's a path.
+        let (host, _) = url.split_once(':')?;
+        if host.contains('/') || host.len() == 1 {
+            // `C:\repo` style drive letters are paths too.
+            return None;
+        }
+        host
+    };
+
+    let host = authority.rsplit('@').next()?;
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split(']').next()?,
+        None => host.split(':').next()?,
+    };
+    (!host.is_empty()).then(|| host.to_string())
+}
+
+fn resolves(host: &str) -> bool {
+    if let Some(&known) = RESOLVED_HOSTS.lock().ok().as_ref().and_then(|hosts| hosts.get(host)) {
+        return known;
+    }
+
+    let resolved = (host, 0).to_socket_addrs().is_ok_and(|mut addrs| addrs.next().is_some());
+    log::debug!("resolving {}: {}", host, if resolved { "ok" } else { "failed" });
+    if let Ok(mut hosts) = RESOLVED_HOSTS.lock() {
+        hosts.insert(host.to_string(), resolved);
+    }
+    resolved
+}
@@ -0,0 +1,94 @@
+use anyhow::Result;
+use colored::*;
+use serde::Serialize;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use crate::{analyze_all, discover_repositories, glyphs, ScanOptions};
+
+/// Min/median/max of one scan phase across all runs, in milliseconds.
+#[derive(Debug, Serialize)]
+pub struct PhaseStats {
+    pub min_ms: f64,
+    pub median_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BenchReport {
+    pub runs: u32,
+    pub repositories: usize,
+    pub walk: PhaseStats,
+    pub analyze: PhaseStats,
+    pub total: PhaseStats,
+}
+
+/// Scan `root` `runs` times, timing directory discovery and repository
+/// analysis separately, and print the spread of each phase.
+pub fn run_bench(root: &PathBuf, options: &ScanOptions, runs: u32, json: bool) -> Result<()> {
+    let mut walk = Vec::new();
+    let mut analyze = Vec::new();
+    let mut total = Vec::new();
+    let mut repositories = 0;
+
+    for run in 1..=runs {
+        let started = Instant::now();
+        let (git_dirs, _) = discover_repositories(root, options);
+        let walked = started.elapsed();
+        let results = analyze_all(root, &git_dirs, options);
+        let finished = started.elapsed();
+
+        log::info!("bench run {}: walk {:?}, analyze {:?}", run, walked, finished - walked);
+        repositories = results.len();
+        walk.push(walked);
+        analyze.push(finished - walked);
+        total.push(finished);
+    }
+
+    let report = BenchReport {
+        runs,
+        repositories,
+        walk: phase_stats(walk),
+        analyze: phase_stats(analyze),
+        total: phase_stats(total),
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!(
+        "{} {} runs over {} repositories in {}\n",
+        glyphs::SEARCH,
+        report.runs,
+        report.repositories,
+        root.display()
+    );
+    println!("{:<10} {:>12} {:>12} {:>12}", "PHASE".bold(), "MIN".bold(), "MEDIAN".bold(), "MAX".bold());
+    for (name, stats) in [("walk", &report.walk), ("analyze", &report.analyze), ("total", &report.total)] {
+        println!(
+            "{:<10} {:>10.2}ms {:>10.2}ms {:>10.2}ms",
+            name, stats.min_ms, stats.median_ms, stats.max_ms
+        );
+    }
+
+    Ok(())
+}
+
+fn phase_stats(mut samples: Vec<Duration>) -> PhaseStats {
+    samples.sort();
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let middle = samples.len() / 2;
+    let median = if samples.len().is_multiple_of(2) {
+        (ms(samples[middle - 1]) + ms(samples[middle])) / 2.0
+    } else {
+        ms(samples[middle])
+    };
+
+    PhaseStats {
+        min_ms: ms(samples[0]),
+        median_ms: median,
+        max_ms: ms(samples[samples.len() - 1]),
+    }
+}
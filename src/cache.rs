@@ -0,0 +1,155 @@
+use anyhow::Result;
+use chrono::Utc;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::dirs;
+
+const GITHUB_CACHE_FILE: &str = "github.json";
+const DIGEST_FILE: &str = "digest.json";
+const UPDATE_FILE: &str = "update.json";
+
+/// On-disk cache of GitHub issue/PR counts, keyed by `owner/repo`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct GitHubCache {
+    entries: HashMap<String, CachedCounts>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedCounts {
+    open_issues: usize,
+    open_prs: usize,
+    fetched_at: i64,
+}
+
+impl GitHubCache {
+    /// Load the cache, starting empty if it is missing or unreadable.
+    pub fn load() -> Self {
+        load_json(GITHUB_CACHE_FILE)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(GITHUB_CACHE_FILE, self)
+    }
+
+    /// Cached `(open_issues, open_prs)` if fetched less than `ttl_secs` ago.
+    pub fn get(&self, key: &str, ttl_secs: u64) -> Option<(usize, usize)> {
+        let entry = self.entries.get(key)?;
+        let age = Utc::now().timestamp() - entry.fetched_at;
+        if age >= 0 && (age as u64) < ttl_secs {
+            Some((entry.open_issues, entry.open_prs))
+        } else {
+            None
+        }
+    }
+
+    pub fn insert(&mut self, key: String, open_issues: usize, open_prs: usize) {
+        self.entries.insert(
+            key,
+            CachedCounts {
+                open_issues,
+                open_prs,
+                fetched_at: Utc::now().timestamp(),
+            },
+        );
+    }
+}
+
+/// State of one repository as recorded by the last `digest` run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoSnapshot {
+    pub is_clean: bool,
+    pub ahead: usize,
+    pub behind: usize,
+    pub branch: Option<String>,
+    pub failed: bool,
+    /// Identifies the repository independently of its path, so a move can
+    /// be told apart from a removal plus an addition. Missing in snapshots
+    /// written by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken_at: i64,
+    /// Keyed by path relative to the scan root.
+    pub repos: BTreeMap<PathBuf, RepoSnapshot>,
+}
+
+/// Digest baselines, one per scan root (canonicalized).
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DigestSnapshots {
+    roots: HashMap<PathBuf, Snapshot>,
+}
+
+impl DigestSnapshots {
+    pub fn load() -> Self {
+        load_json(DIGEST_FILE)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(DIGEST_FILE, self)
+    }
+
+    pub fn get(&self, root: &Path) -> Option<&Snapshot> {
+        self.roots.get(root)
+    }
+
+    pub fn insert(&mut self, root: PathBuf, snapshot: Snapshot) {
+        self.roots.insert(root, snapshot);
+    }
+}
+
+/// The latest release seen by `check-update`, and when it was looked up.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct UpdateCheck {
+    pub latest: Option<String>,
+    pub checked_at: i64,
+}
+
+impl UpdateCheck {
+    pub fn load() -> Self {
+        load_json(UPDATE_FILE)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        save_json(UPDATE_FILE, self)
+    }
+
+    /// The cached latest version if looked up less than `ttl_secs` ago.
+    pub fn fresh(&self, ttl_secs: u64) -> Option<&str> {
+        let age = Utc::now().timestamp() - self.checked_at;
+        (age >= 0 && (age as u64) < ttl_secs).then_some(self.latest.as_deref()).flatten()
+    }
+
+    pub fn record(latest: String) -> Self {
+        Self { latest: Some(latest), checked_at: Utc::now().timestamp() }
+    }
+}
+
+fn cache_file(name: &str) -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join(name))
+}
+
+/// Read a cache file, starting from the default if it is missing or unreadable.
+fn load_json<T: DeserializeOwned + Default>(name: &str) -> T {
+    cache_file(name)
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn save_json<T: Serialize>(name: &str, value: &T) -> Result<()> {
+    let Some(path) = cache_file(name) else {
+        return Ok(());
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, serde_json::to_string(value)?)?;
+    Ok(())
+}
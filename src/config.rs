@@ -1,15 +1,40 @@
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
+use std::process::Command;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     #[serde(default = "default_scan_depth")]
     pub scan_depth: usize,
+
+    /// Directories a walk may visit before a hint about `--depth` and
+    /// ignores is shown; 0 turns the hint off.
+    #[serde(default = "default_walk_hint_after")]
+    pub walk_hint_after: usize,
     
     #[serde(default = "default_ignore_dirs")]
     pub ignore_dirs: Vec<String>,
+
+    /// Skip repositories whose `origin` URL contains one of these strings
+    /// (or matches it, for globs), wherever they are.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ignore_remotes: Vec<String>,
+
+    /// Branch globs (`main`, `release/*`) whose problems are more urgent.
+    #[serde(default)]
+    pub important_branches: Vec<String>,
+
+    /// Branch globs nobody should work on directly (trunk-based workflows):
+    /// local changes or commits there are flagged to move to a feature branch.
+    #[serde(default)]
+    pub protected_branches: Vec<String>,
+
+    /// Path glob -> labels attached to repositories under matching paths.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, Vec<String>>,
     
     #[serde(default)]
     pub github: Option<GitHubConfig>,
@@ -19,6 +44,20 @@ pub struct Config {
     
     #[serde(default)]
     pub export: ExportConfig,
+
+    /// Commands run after every scan with the JSON report on stdin.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hooks: Vec<PostScanHook>,
+
+    /// Bare repositories whose work tree lives elsewhere, like dotfiles kept
+    /// in `~/.dotfiles` with `$HOME` as the work tree.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bare_repos: Vec<BareRepo>,
+
+    /// Loaded from `./.git-nexus.toml`, which may have come with a cloned
+    /// repository: its `[[hooks]]` only run with `--trust-local-config`.
+    #[serde(skip)]
+    pub from_current_dir: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +65,10 @@ pub struct GitHubConfig {
     pub token: Option<String>,
     pub check_issues: bool,
     pub check_prs: bool,
+
+    /// How long cached issue/PR counts stay fresh, in seconds.
+    #[serde(default = "default_github_cache_ttl")]
+    pub cache_ttl: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -38,9 +81,91 @@ pub struct DisplayConfig {
     
     #[serde(default)]
     pub default_verbose: bool,
+
+    #[serde(default = "default_true")]
+    pub show_progress: bool,
+
+    /// Print the "N of M repos need attention" headline before the listing.
+    #[serde(default = "default_true")]
+    pub attention_banner: bool,
+
+    /// The banner turns yellow above this many repositories needing attention...
+    #[serde(default)]
+    pub attention_warn: usize,
+
+    /// ...and red above this many.
+    #[serde(default = "default_attention_alert")]
+    pub attention_alert: usize,
+
+    /// Commit dates: `iso`, `relative` or a strftime pattern.
+    #[serde(default = "default_date_format")]
+    pub date_format: String,
+
+    /// Time zone for commit dates: `local`, `utc` or an offset like `+05:30`.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+
+    /// Replacements for the symbols and status labels of the listing.
+    #[serde(default)]
+    pub icons: IconsConfig,
+}
+
+/// `[display.icons]`: each key replaces one symbol or label in the listing;
+/// unset keys keep the default (or its `--ascii` fallback).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IconsConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clean: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dirty: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ahead: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub behind: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stash: Option<String>,
+}
+
+impl IconsConfig {
+    /// Overrides are printed inline, so a line break would split a
+    /// repository's line in two.
+    pub fn validate(&self) -> Result<()> {
+        let icons = [
+            ("repo", &self.repo),
+            ("clean", &self.clean),
+            ("dirty", &self.dirty),
+            ("error", &self.error),
+            ("ahead", &self.ahead),
+            ("behind", &self.behind),
+            ("stash", &self.stash),
+        ];
+        for (key, value) in icons {
+            if let Some(value) = value
+                && value.contains(['\n', '\r'])
+            {
+                anyhow::bail!("display.icons.{} must not contain line breaks", key);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PostScanHook {
+    pub command: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BareRepo {
+    pub git_dir: PathBuf,
+    pub work_tree: PathBuf,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExportConfig {
     pub default_format: Option<String>,
     pub html_template: Option<PathBuf>,
@@ -50,6 +175,10 @@ fn default_scan_depth() -> usize {
     3
 }
 
+fn default_walk_hint_after() -> usize {
+    50_000
+}
+
 fn default_ignore_dirs() -> Vec<String> {
     vec![
         "node_modules".to_string(),
@@ -62,6 +191,22 @@ fn default_ignore_dirs() -> Vec<String> {
     ]
 }
 
+fn default_github_cache_ttl() -> u64 {
+    60 * 60
+}
+
+fn default_attention_alert() -> usize {
+    5
+}
+
+fn default_date_format() -> String {
+    crate::dates::DEFAULT_FORMAT.to_string()
+}
+
+fn default_timezone() -> String {
+    "local".to_string()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -70,37 +215,80 @@ impl Default for Config {
     fn default() -> Self {
         Self {
             scan_depth: default_scan_depth(),
+            walk_hint_after: default_walk_hint_after(),
             ignore_dirs: default_ignore_dirs(),
+            ignore_remotes: Vec::new(),
+            important_branches: Vec::new(),
+            protected_branches: Vec::new(),
+            labels: BTreeMap::new(),
             github: None,
             display: DisplayConfig::default(),
             export: ExportConfig::default(),
+            hooks: Vec::new(),
+            bare_repos: Vec::new(),
+            from_current_dir: false,
         }
     }
 }
 
-impl Default for DisplayConfig {
+impl Default for GitHubConfig {
     fn default() -> Self {
         Self {
-            show_branch: true,
-            show_colors: true,
-            default_verbose: false,
+            token: None,
+            check_issues: true,
+            check_prs: true,
+            cache_ttl: default_github_cache_ttl(),
         }
     }
 }
 
-impl Default for ExportConfig {
+impl GitHubConfig {
+    /// Resolve the API token, in order: the config `token`, the
+    /// `GITHUB_TOKEN` environment variable, then the GitHub CLI's stored
+    /// credentials (`gh auth token`). Empty values are skipped; `None` means
+    /// requests go out unauthenticated.
+    pub fn resolve_token(&self) -> Option<String> {
+        let non_empty = |token: String| {
+            let token = token.trim().to_string();
+            (!token.is_empty()).then_some(token)
+        };
+
+        self.token
+            .clone()
+            .and_then(non_empty)
+            .or_else(|| std::env::var("GITHUB_TOKEN").ok().and_then(non_empty))
+            .or_else(|| {
+                let output = Command::new("gh").args(["auth", "token"]).output().ok()?;
+                if !output.status.success() {
+                    return None;
+                }
+                non_empty(String::from_utf8_lossy(&output.stdout).into_owned())
+            })
+    }
+}
+
+impl Default for DisplayConfig {
     fn default() -> Self {
         Self {
-            default_format: None,
-            html_template: None,
+            show_branch: true,
+            show_colors: true,
+            default_verbose: false,
+            show_progress: true,
+            attention_banner: true,
+            attention_warn: 0,
+            attention_alert: default_attention_alert(),
+            date_format: default_date_format(),
+            timezone: default_timezone(),
+            icons: IconsConfig::default(),
         }
     }
 }
 
 impl Config {
     pub fn load() -> Result<Self> {
+        let local = PathBuf::from(".git-nexus.toml");
         let config_paths = vec![
-            PathBuf::from(".git-nexus.toml"),
+            local.clone(),
             dirs::home_dir()
                 .map(|h| h.join(".config/git-nexus/config.toml"))
                 .unwrap_or_default(),
@@ -112,7 +300,13 @@ impl Config {
         for path in config_paths {
             if path.exists() {
                 let contents = fs::read_to_string(&path)?;
-                let config: Config = toml::from_str(&contents)?;
+                let mut config: Config = toml::from_str(&contents)?;
+                config.export.html_template = config.export.html_template.map(|p| dirs::expand(&p));
+                for bare in &mut config.bare_repos {
+                    bare.git_dir = dirs::expand(&bare.git_dir);
+                    bare.work_tree = dirs::expand(&bare.work_tree);
+                }
+                config.from_current_dir = path == local;
                 return Ok(config);
             }
         }
@@ -133,12 +327,44 @@ impl Config {
 }
 
 // Add dirs dependency helper
-mod dirs {
-    use std::path::PathBuf;
-    
+pub mod dirs {
+    use regex::{Captures, Regex};
+    use std::path::{Path, PathBuf};
+    use std::sync::LazyLock;
+
+    static VARIABLE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\$(?:\{(\w+)\}|(\w+))").unwrap());
+
     pub fn home_dir() -> Option<PathBuf> {
         std::env::var_os("HOME")
             .or_else(|| std::env::var_os("USERPROFILE"))
             .map(PathBuf::from)
     }
+
+    pub fn cache_dir() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| home_dir().map(|h| h.join(".cache")))
+            .map(|c| c.join("git-nexus"))
+    }
+
+    /// Expand a leading `~` and `$VAR` / `${VAR}` references, for paths that
+    /// never went through a shell: config values, quoted arguments and
+    /// `--flag=~/path`. Unset variables are left as written.
+    pub fn expand(path: &Path) -> PathBuf {
+        let Some(text) = path.to_str() else {
+            return path.to_path_buf();
+        };
+
+        let text = match (text.strip_prefix('~'), home_dir()) {
+            (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+                format!("{}{}", home.display(), rest)
+            }
+            _ => text.to_string(),
+        };
+        let expanded = VARIABLE.replace_all(&text, |caps: &Captures| {
+            let name = caps.get(1).or_else(|| caps.get(2)).map_or("", |m| m.as_str());
+            std::env::var(name).unwrap_or_else(|_| caps[0].to_string())
+        });
+        PathBuf::from(expanded.into_owned())
+    }
 }
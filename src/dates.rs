@@ -0,0 +1,150 @@
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Utc};
+
+/// The format used when neither `--date` nor `date_format` is set.
+pub const DEFAULT_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// How commit times are rendered in every output (terminal, JSON, CSV, HTML).
+#[derive(Debug, Clone)]
+pub enum DateFormat {
+    /// RFC 3339 in UTC, e.g. `2024-05-01T09:30:00Z`.
+    Iso,
+    /// Age relative to now, e.g. `3 days ago`.
+    Relative,
+    /// A strftime pattern, applied in the configured time zone.
+    Strftime(String),
+}
+
+/// Time zone that strftime dates are shown in.
+#[derive(Debug, Clone, Copy)]
+pub enum Zone {
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl Zone {
+    /// Parse `local`, `utc` or an offset such as `+05:30` / `-08:00`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid time zone `{}` (expected local, utc or an offset like +05:30)", spec);
+        match spec.to_ascii_lowercase().as_str() {
+            "local" => return Ok(Self::Local),
+            "utc" => return Ok(Self::Utc),
+            _ => {}
+        }
+
+        let (sign, offset) = match spec.split_at_checked(1) {
+            Some(("+", rest)) => (1, rest),
+            Some(("-", rest)) => (-1, rest),
+            _ => return Err(invalid()),
+        };
+        let (hours, minutes) = offset.split_once(':').ok_or_else(invalid)?;
+        let hours: i32 = hours.parse().map_err(|_| invalid())?;
+        let minutes: i32 = minutes.parse().map_err(|_| invalid())?;
+        if hours > 23 || minutes > 59 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(Self::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl DateFormat {
+    /// Parse `iso`, `relative` or a strftime pattern, rejecting patterns
+    /// chrono cannot format.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec {
+            "iso" => Ok(Self::Iso),
+            "relative" => Ok(Self::Relative),
+            pattern => {
+                if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+                    return Err(format!("invalid date format `{}` (expected iso, relative or a strftime pattern)", pattern));
+                }
+                Ok(Self::Strftime(pattern.to_string()))
+            }
+        }
+    }
+
+    /// Render a Unix timestamp; `None` if it is out of chrono's range.
+    /// `iso` is always UTC, so `zone` only affects strftime patterns.
+    pub fn format(&self, seconds: i64, zone: Zone) -> Option<String> {
+        let utc = DateTime::<Utc>::from_timestamp(seconds, 0)?;
+        Some(match self {
+            Self::Iso => utc.to_rfc3339_opts(SecondsFormat::Secs, true),
+            Self::Relative => relative(Utc::now().timestamp() - seconds),
+            Self::Strftime(pattern) => match zone {
+                Zone::Local => utc.with_timezone(&Local).format(pattern).to_string(),
+                Zone::Utc => utc.format(pattern).to_string(),
+                Zone::Fixed(offset) => utc.with_timezone(&offset).format(pattern).to_string(),
+            },
+        })
+    }
+}
+
+/// `age` seconds as "N units ago"; times in the future (clock skew) are "just now".
+fn relative(age: i64) -> String {
+    const UNITS: [(i64, &str); 6] = [
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (7 * 86_400, "week"),
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+    ];
+
+    for (size, unit) in UNITS {
+        let count = age / size;
+        if count == 1 {
+            return format!("1 {} ago", unit);
+        } else if count > 1 {
+            return format!("{} {}s ago", count, unit);
+        }
+    }
+    "just now".to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// 2024-05-01 09:30:00 UTC.
+    const COMMIT_TIME: i64 = 1_714_555_800;
+
+    fn strftime() -> DateFormat {
+        DateFormat::parse("%Y-%m-%d %H:%M %z").unwrap()
+    }
+
+    #[test]
+    fn formats_in_utc() {
+        assert_eq!(strftime().format(COMMIT_TIME, Zone::Utc).as_deref(), Some("2024-05-01 09:30 +0000"));
+    }
+
+    #[test]
+    fn formats_in_fixed_offsets() {
+        let ahead = Zone::parse("+05:30").unwrap();
+        let behind = Zone::parse("-08:00").unwrap();
+        assert_eq!(strftime().format(COMMIT_TIME, ahead).as_deref(), Some("2024-05-01 15:00 +0530"));
+        assert_eq!(strftime().format(COMMIT_TIME, behind).as_deref(), Some("2024-05-01 01:30 -0800"));
+    }
+
+    #[test]
+    fn formats_in_the_local_zone() {
+        let expected = Local.timestamp_opt(COMMIT_TIME, 0).unwrap().format("%Y-%m-%d %H:%M %z").to_string();
+        assert_eq!(strftime().format(COMMIT_TIME, Zone::Local), Some(expected));
+    }
+
+    #[test]
+    fn iso_ignores_the_zone() {
+        let zone = Zone::parse("+05:30").unwrap();
+        assert_eq!(DateFormat::Iso.format(COMMIT_TIME, zone).as_deref(), Some("2024-05-01T09:30:00Z"));
+    }
+
+    #[test]
+    fn rejects_invalid_zones() {
+        for spec in ["", "5:30", "+24:00", "+05:60", "+05", "mars"] {
+            assert!(Zone::parse(spec).is_err(), "{} should be rejected", spec);
+        }
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::Result;
+use chrono::{Local, TimeZone, Utc};
+use colored::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::cache::{DigestSnapshots, RepoSnapshot, Snapshot};
+use crate::{glyphs, RepoStatus};
+
+/// Repositories listed under "top movers".
+const TOP_MOVERS: usize = 5;
+
+/// What changed across the workspace since the previous `digest` run.
+#[derive(Debug, Default, Serialize)]
+pub struct Digest {
+    /// When the previous snapshot was taken; `None` on the first run.
+    pub since: Option<String>,
+    pub repos: usize,
+    pub added: Vec<PathBuf>,
+    pub removed: Vec<PathBuf>,
+    pub moved: Vec<Move>,
+    pub newly_dirty: Vec<PathBuf>,
+    pub newly_clean: Vec<PathBuf>,
+    pub newly_ahead: Vec<PathBuf>,
+    pub newly_behind: Vec<PathBuf>,
+    pub newly_failing: Vec<PathBuf>,
+    pub recovered: Vec<PathBuf>,
+    pub branch_changes: Vec<BranchChange>,
+    pub top_movers: Vec<Mover>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchChange {
+    pub path: PathBuf,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// A repository found at a new path with the same remote (or, without one,
+/// the same root commit) as one that disappeared.
+#[derive(Debug, Serialize)]
+pub struct Move {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// A repository whose ahead/behind counts moved since the last run.
+#[derive(Debug, Serialize)]
+pub struct Mover {
+    pub path: PathBuf,
+    pub ahead_delta: i64,
+    pub behind_delta: i64,
+}
+
+/// Compare a fresh scan of `root` against the stored snapshot, print the
+/// digest and store the scan as the new baseline.
+pub fn run_digest(root: &Path, repos: &[RepoStatus], json: bool) -> Result<()> {
+    let key = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let current = snapshot(root, repos);
+
+    let mut snapshots = DigestSnapshots::load();
+    let digest = match snapshots.get(&key) {
+        Some(previous) => compare(previous, &current),
+        None => Digest { repos: current.repos.len(), ..Digest::default() },
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&digest)?);
+    } else {
+        print_digest(&digest);
+    }
+
+    snapshots.insert(key, current);
+    snapshots.save()
+}
+
+fn snapshot(root: &Path, repos: &[RepoStatus]) -> Snapshot {
+    Snapshot {
+        taken_at: Utc::now().timestamp(),
+        repos: repos
+            .iter()
+            .map(|repo| {
+                let relative = repo.path.strip_prefix(root).unwrap_or(&repo.path).to_path_buf();
+                let state = RepoSnapshot {
+                    is_clean: repo.is_clean,
+                    ahead: repo.ahead,
+                    behind: repo.behind,
+                    branch: repo.branch.clone(),
+                    failed: repo.error.is_some(),
+                    id: stable_id(&repo.path),
+                };
+                (relative, state)
+            })
+            .collect(),
+    }
+}
+
+/// The origin URL (or the first remote's), or for a repository without
+/// remotes its root commit. Walking to the root commit reads the whole
+/// history, which is only done for local-only repositories.
+fn stable_id(path: &Path) -> Option<String> {
+    let repo = git2::Repository::open(path).ok()?;
+    let remote = repo.find_remote("origin").ok().or_else(|| {
+        let names = repo.remotes().ok()?;
+        let name = names.iter().flatten().next()?;
+        repo.find_remote(name).ok()
+    });
+    if let Some(url) = remote.as_ref().and_then(|r| r.url()) {
+        return Some(format!("remote:{}", url));
+    }
+
+    let mut walk = repo.revwalk().ok()?;
+    walk.push_head().ok()?;
+    walk.simplify_first_parent().ok()?;
+    let root = walk.flatten().last()?;
+    Some(format!("root:{}", root))
+}
+
+/// Pair up repositories that disappeared with ones that appeared under the
+/// same id, keyed by new path. Ids shared by several candidates on either
+/// side (e.g. two clones of one remote) are ambiguous and left as
+/// removed/added.
+fn detect_moves(previous: &Snapshot, current: &Snapshot) -> HashMap<PathBuf, PathBuf> {
+    fn by_id<'a>(repos: impl Iterator<Item = (&'a PathBuf, &'a RepoSnapshot)>) -> HashMap<&'a str, Vec<&'a PathBuf>> {
+        let mut ids: HashMap<&str, Vec<&PathBuf>> = HashMap::new();
+        for (path, repo) in repos {
+            if let Some(id) = repo.id.as_deref() {
+                ids.entry(id).or_default().push(path);
+            }
+        }
+        ids
+    }
+
+    let gone = by_id(previous.repos.iter().filter(|(path, _)| !current.repos.contains_key(*path)));
+    let new = by_id(current.repos.iter().filter(|(path, _)| !previous.repos.contains_key(*path)));
+
+    new.iter()
+        .filter_map(|(id, to)| match (to.as_slice(), gone.get(id).map(Vec::as_slice)) {
+            ([to], Some([from])) => Some(((*to).clone(), (*from).clone())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn compare(previous: &Snapshot, current: &Snapshot) -> Digest {
+    let moves = detect_moves(previous, current);
+    let mut digest = Digest {
+        since: Local
+            .timestamp_opt(previous.taken_at, 0)
+            .single()
+            .map(|t| t.format("%Y-%m-%d %H:%M").to_string()),
+        repos: current.repos.len(),
+        removed: previous
+            .repos
+            .keys()
+            .filter(|path| !current.repos.contains_key(*path) && !moves.values().any(|from| from == *path))
+            .cloned()
+            .collect(),
+        ..Digest::default()
+    };
+
+    for (path, now) in &current.repos {
+        // A moved repository is compared with its state at the old path.
+        let before = previous.repos.get(path).or_else(|| {
+            let from = moves.get(path)?;
+            digest.moved.push(Move { from: from.clone(), to: path.clone() });
+            previous.repos.get(from)
+        });
+        let Some(before) = before else {
+            digest.added.push(path.clone());
+            continue;
+        };
+
+        match (before.failed, now.failed) {
+            (false, true) => digest.newly_failing.push(path.clone()),
+            (true, false) => digest.recovered.push(path.clone()),
+            _ => {}
+        }
+        if now.failed {
+            continue;
+        }
+
+        if before.is_clean && !now.is_clean {
+            digest.newly_dirty.push(path.clone());
+        } else if !before.is_clean && now.is_clean && !before.failed {
+            digest.newly_clean.push(path.clone());
+        }
+        if before.ahead == 0 && now.ahead > 0 {
+            digest.newly_ahead.push(path.clone());
+        }
+        if before.behind == 0 && now.behind > 0 {
+            digest.newly_behind.push(path.clone());
+        }
+        if !before.failed && before.branch != now.branch {
+            digest.branch_changes.push(BranchChange {
+                path: path.clone(),
+                from: before.branch.clone(),
+                to: now.branch.clone(),
+            });
+        }
+
+        let ahead_delta = now.ahead as i64 - before.ahead as i64;
+        let behind_delta = now.behind as i64 - before.behind as i64;
+        if ahead_delta != 0 || behind_delta != 0 {
+            digest.top_movers.push(Mover { path: path.clone(), ahead_delta, behind_delta });
+        }
+    }
+
+    digest
+        .top_movers
+        .sort_by_key(|m| std::cmp::Reverse(m.ahead_delta.abs() + m.behind_delta.abs()));
+    digest.top_movers.truncate(TOP_MOVERS);
+
+    digest
+}
+
+fn print_digest(digest: &Digest) {
+    let Some(since) = &digest.since else {
+        println!(
+            "{} No previous snapshot; recorded a baseline of {} repositories.",
+            glyphs::DONE,
+            digest.repos
+        );
+        println!("   Run `git-nexus digest` again later to see what changed.");
+        return;
+    };
+
+    println!("{}", format!("Workspace digest since {}", since).bright_cyan().bold());
+    println!(
+        "{} repositories: {} newly dirty, {} newly clean, {} newly ahead, {} newly behind\n",
+        digest.repos,
+        digest.newly_dirty.len().to_string().red(),
+        digest.newly_clean.len().to_string().green(),
+        digest.newly_ahead.len().to_string().yellow(),
+        digest.newly_behind.len().to_string().red()
+    );
+
+    print_section("Added", &digest.added);
+    print_section("Removed", &digest.removed);
+
+    if !digest.moved.is_empty() {
+        println!("{}", "Moved".bold());
+        for change in &digest.moved {
+            println!(
+                "   {} {} {} {}",
+                glyphs::TREE.get().bright_black(),
+                change.from.display(),
+                glyphs::ARROW,
+                change.to.display()
+            );
+        }
+    }
+    print_section("Newly dirty", &digest.newly_dirty);
+    print_section("Newly clean", &digest.newly_clean);
+    print_section("Newly ahead", &digest.newly_ahead);
+    print_section("Newly behind", &digest.newly_behind);
+    print_section("Started failing", &digest.newly_failing);
+    print_section("Recovered", &digest.recovered);
+
+    if !digest.branch_changes.is_empty() {
+        println!("{}", "Branch changes".bold());
+        for change in &digest.branch_changes {
+            println!(
+                "   {} {}: {} {} {}",
+                glyphs::TREE.get().bright_black(),
+                change.path.display(),
+                change.from.as_deref().unwrap_or("-"),
+                glyphs::ARROW,
+                change.to.as_deref().unwrap_or("-")
+            );
+        }
+    }
+
+    if !digest.top_movers.is_empty() {
+        println!("{}", "Top movers".bold());
+        for mover in &digest.top_movers {
+            println!(
+                "   {} {}: {}{:+} {}{:+}",
+                glyphs::TREE.get().bright_black(),
+                mover.path.display(),
+                glyphs::AHEAD,
+                mover.ahead_delta,
+                glyphs::BEHIND,
+                mover.behind_delta
+            );
+        }
+    }
+
+    let unchanged = digest.added.is_empty()
+        && digest.removed.is_empty()
+        && digest.moved.is_empty()
+        && digest.newly_dirty.is_empty()
+        && digest.newly_clean.is_empty()
+        && digest.newly_ahead.is_empty()
+        && digest.newly_behind.is_empty()
+        && digest.newly_failing.is_empty()
+        && digest.recovered.is_empty()
+        && digest.branch_changes.is_empty()
+        && digest.top_movers.is_empty();
+    if unchanged {
+        println!("{} Nothing changed.", glyphs::DONE);
+    }
+}
+
+fn print_section(title: &str, paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+    println!("{}", title.bold());
+    for path in paths {
+        println!("   {} {}", glyphs::TREE.get().bright_black(), path.display());
+    }
+}
@@ -1,15 +1,16 @@
 use anyhow::Result;
-use chrono::Local;
+use chrono::{Local, Utc};
 use csv::Writer;
-use std::fs::File;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
+use crate::summary::IssueSummary;
 use crate::RepoStatus;
 
-pub fn export_csv(repos: &[RepoStatus], path: &PathBuf) -> Result<()> {
+pub fn export_csv(repos: &[RepoStatus], root: Option<&Path>, path: &PathBuf) -> Result<()> {
     let mut wtr = Writer::from_path(path)?;
     
-    wtr.write_record(&[
+    wtr.write_record([
         "Path",
         "Branch",
         "Status",
@@ -22,13 +23,15 @@ pub fn export_csv(repos: &[RepoStatus], path: &PathBuf) -> Result<()> {
         "Last Commit Author",
         "Last Commit Message",
         "Last Commit Timestamp",
+        "Labels",
+        "Last Commit Email",
     ])?;
 
     for repo in repos {
         wtr.write_record(&[
-            repo.path.display().to_string(),
+            repo.display_path(root),
             repo.branch.as_deref().unwrap_or("N/A").to_string(),
-            if repo.is_clean { "CLEAN" } else { "DIRTY" }.to_string(),
+            repo.status_label().to_string(),
             repo.ahead.to_string(),
             repo.behind.to_string(),
             repo.stash_count.map(|c| c.to_string()).unwrap_or_default(),
@@ -38,6 +41,8 @@ pub fn export_csv(repos: &[RepoStatus], path: &PathBuf) -> Result<()> {
             repo.last_commit.as_ref().map(|c| c.author.clone()).unwrap_or_default(),
             repo.last_commit.as_ref().map(|c| c.message.clone()).unwrap_or_default(),
             repo.last_commit.as_ref().map(|c| c.timestamp.clone()).unwrap_or_default(),
+            repo.labels.join(";"),
+            repo.last_commit.as_ref().map(|c| c.email.clone()).unwrap_or_default(),
         ])?;
     }
 
@@ -45,58 +50,159 @@ pub fn export_csv(repos: &[RepoStatus], path: &PathBuf) -> Result<()> {
     Ok(())
 }
 
-pub fn export_html(repos: &[RepoStatus], path: &PathBuf) -> Result<()> {
-    let html = generate_html(repos)?;
+/// The dashboard numbers of a report: the attention counts plus commit and
+/// stash totals and the average health score.
+struct Totals {
+    issues: IssueSummary,
+    clean: usize,
+    commits_ahead: usize,
+    commits_behind: usize,
+    stashes: usize,
+    /// Over the analyzed repositories; None when there are none.
+    average_health: Option<u8>,
+}
+
+impl Totals {
+    fn from_repos(repos: &[RepoStatus]) -> Self {
+        let analyzed = || repos.iter().filter(|r| r.error.is_none());
+        Self {
+            issues: IssueSummary::from_repos(repos),
+            clean: analyzed().filter(|r| r.is_clean).count(),
+            commits_ahead: analyzed().map(|r| r.ahead).sum(),
+            commits_behind: analyzed().map(|r| r.behind).sum(),
+            stashes: analyzed().filter_map(|r| r.stash_count).sum(),
+            average_health: crate::health::average(
+                analyzed().map(|r| r.health.unwrap_or_else(|| crate::health::calculate_health_score(r))),
+            ),
+        }
+    }
+}
+
+/// `export csv --summary-only`: a header and a single row of totals, so
+/// snapshots taken over time can be appended into one sheet.
+pub fn export_summary_csv(repos: &[RepoStatus], path: &PathBuf) -> Result<()> {
+    let totals = Totals::from_repos(repos);
+    let mut wtr = Writer::from_path(path)?;
+
+    wtr.write_record([
+        "Generated",
+        "Total",
+        "Clean",
+        "Dirty",
+        "Needs Attention",
+        "Repos Ahead",
+        "Commits Ahead",
+        "Repos Behind",
+        "Commits Behind",
+        "Detached",
+        "Repos With Stashes",
+        "Stashes",
+        "Average Health",
+    ])?;
+    wtr.write_record(&[
+        Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        totals.issues.total.to_string(),
+        totals.clean.to_string(),
+        totals.issues.dirty.to_string(),
+        totals.issues.needs_attention.to_string(),
+        totals.issues.ahead.to_string(),
+        totals.commits_ahead.to_string(),
+        totals.issues.behind.to_string(),
+        totals.commits_behind.to_string(),
+        totals.issues.detached.to_string(),
+        totals.issues.stashed.to_string(),
+        totals.stashes.to_string(),
+        totals.average_health.map(|h| h.to_string()).unwrap_or_default(),
+    ])?;
+
+    wtr.flush()?;
+    Ok(())
+}
+
+/// Optional sections of the HTML report.
+#[derive(Debug, Clone, Default)]
+pub struct HtmlOptions {
+    /// Add a commit-recency overview above the table.
+    pub activity: bool,
+    /// Split the table into anchored sections with a table of contents.
+    pub group: Option<HtmlGroup>,
+    /// The scanned directory, for grouping by top-level directory.
+    pub scan_root: PathBuf,
+    /// Color scheme of the report.
+    pub theme: HtmlTheme,
+    /// Shorten longer repository paths in the middle (`--max-path-len`).
+    pub max_path_len: Option<usize>,
+    /// Only the dashboard numbers, without the repository table (or, for
+    /// CSV, a single row of totals).
+    pub summary_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+pub enum HtmlGroup {
+    /// Dirty, then failed, then clean repositories
+    Status,
+    /// First directory below the scan root
+    Directory,
+}
+
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+pub enum HtmlTheme {
+    /// Always light
+    Light,
+    /// Always dark
+    Dark,
+    /// Follow the viewer's OS setting
+    #[default]
+    Auto,
+}
+
+/// Overrides for the dark theme. Badge colors keep at least 7:1 contrast
+/// between text and background, like the light ones.
+const DARK_CSS: &str = r#"
+        :root { color-scheme: dark; }
+        body { background: linear-gradient(135deg, #1f2340 0%, #2b1d3d 100%); color: #e1e1e6; }
+        .container { background: #1c1c22; box-shadow: 0 20px 60px rgba(0,0,0,0.6); }
+        .header { background: linear-gradient(135deg, #3b4185 0%, #4a2f66 100%); }
+        .stats, th, .footer { background: #25252d; }
+        .stats, th, td, .activity, .toc { border-color: #3a3a45; }
+        tr:hover { background: #26262f; }
+        .stat-value, .toc a, .top-link { color: #a5b4fc; }
+        .stat-label, .activity-legend { color: #a0a0ab; }
+        th, .activity h2, .toc h2, .group h2 { color: #d0d0d8; }
+        small, .footer { color: #9a9aa5; }
+        .badge-clean { background: #163d1f; color: #b9f3c5; }
+        .badge-dirty, .badge-danger { background: #4d1a20; color: #fcc9ce; }
+        .badge-error, .badge-warning { background: #463708; color: #ffe39a; }
+        .badge-info { background: #0c3b45; color: #c0ecf5; }
+"#;
+
+fn theme_css(theme: HtmlTheme) -> String {
+    match theme {
+        HtmlTheme::Light => String::new(),
+        HtmlTheme::Dark => DARK_CSS.to_string(),
+        HtmlTheme::Auto => format!("@media (prefers-color-scheme: dark) {{{}}}", DARK_CSS),
+    }
+}
+
+pub fn export_html(repos: &[RepoStatus], root: Option<&Path>, path: &PathBuf, options: &HtmlOptions) -> Result<()> {
+    let html = generate_html(repos, root, options)?;
     std::fs::write(path, html)?;
     Ok(())
 }
 
-fn generate_html(repos: &[RepoStatus]) -> Result<String> {
+fn generate_html(repos: &[RepoStatus], root: Option<&Path>, options: &HtmlOptions) -> Result<String> {
     let now = Local::now().format("%Y-%m-%d %H:%M:%S");
+    let activity = if options.activity && !options.summary_only {
+        activity_html(repos, root)
+    } else {
+        String::new()
+    };
     
-    let mut rows = String::new();
-    for repo in repos {
-        let status_class = if repo.is_clean { "clean" } else { "dirty" };
-        let status_text = if repo.is_clean { "CLEAN" } else { "DIRTY" };
-        
-        let ahead_badge = if repo.ahead > 0 {
-            format!("<span class=\"badge badge-warning\">↑{}</span>", repo.ahead)
-        } else {
-            String::new()
-        };
-        
-        let behind_badge = if repo.behind > 0 {
-            format!("<span class=\"badge badge-danger\">↓{}</span>", repo.behind)
-        } else {
-            String::new()
-        };
-        
-        let last_commit = if let Some(ref commit) = repo.last_commit {
-            format!(
-                "<small>{} · {} · {}</small>",
-                commit.hash, commit.author, commit.message
-            )
-        } else {
-            String::new()
-        };
-        
-        rows.push_str(&format!(
-            r#"<tr>
-                <td><strong>{}</strong></td>
-                <td><span class="badge badge-info">{}</span></td>
-                <td><span class="badge badge-{}">{}</span></td>
-                <td>{} {}</td>
-                <td>{}</td>
-            </tr>"#,
-            repo.path.display(),
-            repo.branch.as_deref().unwrap_or("N/A"),
-            status_class,
-            status_text,
-            ahead_badge,
-            behind_badge,
-            last_commit
-        ));
-    }
+    let tables = match options.group {
+        _ if options.summary_only => String::new(),
+        None => table_html(repos.iter(), root, options.max_path_len),
+        Some(group) => grouped_html(repos, root, group, options),
+    };
 
     Ok(format!(
         r#"<!DOCTYPE html>
@@ -137,6 +243,8 @@ fn generate_html(repos: &[RepoStatus]) -> Result<String> {
         }}
         .stats {{
             display: flex;
+            flex-wrap: wrap;
+            gap: 20px;
             justify-content: space-around;
             padding: 30px;
             background: #f8f9fa;
@@ -189,6 +297,10 @@ fn generate_html(repos: &[RepoStatus]) -> Result<String> {
             background: #f8d7da;
             color: #721c24;
         }}
+        .badge-error {{
+            background: #fff3cd;
+            color: #856404;
+        }}
         .badge-info {{
             background: #d1ecf1;
             color: #0c5460;
@@ -204,12 +316,83 @@ fn generate_html(repos: &[RepoStatus]) -> Result<String> {
         small {{
             color: #6c757d;
         }}
+        .activity {{
+            padding: 30px;
+            border-bottom: 1px solid #dee2e6;
+        }}
+        .activity h2 {{
+            font-size: 1.2em;
+            margin-bottom: 15px;
+            color: #495057;
+        }}
+        .activity-bar {{
+            display: flex;
+            height: 24px;
+            border-radius: 6px;
+            overflow: hidden;
+            margin-bottom: 10px;
+        }}
+        .activity-legend {{
+            display: flex;
+            flex-wrap: wrap;
+            gap: 16px;
+            margin-bottom: 20px;
+            color: #666;
+            font-size: 0.9em;
+        }}
+        .activity-tiles {{
+            display: flex;
+            flex-wrap: wrap;
+            gap: 4px;
+        }}
+        .tile {{
+            width: 18px;
+            height: 18px;
+            border-radius: 3px;
+            display: inline-block;
+        }}
+        .legend-swatch {{
+            width: 12px;
+            height: 12px;
+            border-radius: 2px;
+            display: inline-block;
+            margin-right: 6px;
+            vertical-align: middle;
+        }}
+        .toc {{
+            padding: 20px 30px;
+            border-bottom: 1px solid #dee2e6;
+        }}
+        .toc h2, .group h2 {{
+            font-size: 1.2em;
+            color: #495057;
+            margin-bottom: 10px;
+        }}
+        .toc ul {{
+            list-style: none;
+            display: flex;
+            flex-wrap: wrap;
+            gap: 8px 24px;
+        }}
+        .toc a, .top-link {{
+            color: #667eea;
+            text-decoration: none;
+        }}
+        .group h2 {{
+            padding: 20px 16px 0;
+        }}
+        .top-link {{
+            font-size: 0.7em;
+            font-weight: normal;
+            margin-left: 8px;
+        }}
         .footer {{
             text-align: center;
             padding: 20px;
             color: #6c757d;
             background: #f8f9fa;
         }}
+        {}
     </style>
 </head>
 <body>
@@ -218,21 +401,112 @@ fn generate_html(repos: &[RepoStatus]) -> Result<String> {
             <h1>🚀 Git Nexus Report</h1>
             <p>Repository Status Overview · Generated on {}</p>
         </div>
-        <div class="stats">
-            <div class="stat">
-                <div class="stat-value">{}</div>
-                <div class="stat-label">Total Repositories</div>
-            </div>
-            <div class="stat">
-                <div class="stat-value">{}</div>
-                <div class="stat-label">Clean</div>
-            </div>
+        <div class="stats">{}
+        </div>
+        {}
+        {}
+        <div class="footer">
+            Generated by <strong>git-nexus</strong> · A blazing fast multi-repository scanner
+        </div>
+    </div>
+</body>
+</html>"#,
+        now,
+        theme_css(options.theme),
+        now,
+        stats_html(repos, options.summary_only),
+        activity,
+        tables
+    ))
+}
+
+/// The dashboard tiles: total, clean and dirty, and with `--summary-only`
+/// the rest of the totals as well, since there is no table to read them from.
+fn stats_html(repos: &[RepoStatus], summary_only: bool) -> String {
+    let totals = Totals::from_repos(repos);
+    let mut stats = vec![
+        ("Total Repositories", totals.issues.total.to_string()),
+        ("Clean", totals.clean.to_string()),
+        ("Dirty", totals.issues.dirty.to_string()),
+    ];
+    if summary_only {
+        stats.extend([
+            ("Need Attention", totals.issues.needs_attention.to_string()),
+            ("Commits Ahead", format!("{} <small>in {} repos</small>", totals.commits_ahead, totals.issues.ahead)),
+            ("Commits Behind", format!("{} <small>in {} repos</small>", totals.commits_behind, totals.issues.behind)),
+            ("Detached", totals.issues.detached.to_string()),
+            ("Stashes", format!("{} <small>in {} repos</small>", totals.stashes, totals.issues.stashed)),
+            ("Average Health", totals.average_health.map_or("–".to_string(), |h| h.to_string())),
+        ]);
+    }
+
+    stats
+        .into_iter()
+        .map(|(label, value)| {
+            format!(
+                r#"
             <div class="stat">
                 <div class="stat-value">{}</div>
-                <div class="stat-label">Dirty</div>
-            </div>
-        </div>
-        <table>
+                <div class="stat-label">{}</div>
+            </div>"#,
+                value, label
+            )
+        })
+        .collect()
+}
+
+fn table_row(repo: &RepoStatus, root: Option<&Path>, max_path_len: Option<usize>) -> String {
+    let status_text = repo.status_label();
+    let status_class = status_text.to_lowercase();
+    
+    let ahead_badge = if repo.ahead > 0 {
+        format!("<span class=\"badge badge-warning\">↑{}</span>", repo.ahead)
+    } else {
+        String::new()
+    };
+    
+    let behind_badge = if repo.behind > 0 {
+        format!("<span class=\"badge badge-danger\">↓{}</span>", repo.behind)
+    } else {
+        String::new()
+    };
+    
+    let last_commit = if let Some(ref error) = repo.error {
+        format!("<small>⚠ {}</small>", escape_html(error))
+    } else if let Some(ref commit) = repo.last_commit {
+        format!(
+            "<small>{} · {} · {}</small>",
+            escape_html(&commit.hash),
+            escape_html(&commit.author),
+            escape_html(&commit.message)
+        )
+    } else {
+        String::new()
+    };
+    
+    format!(
+        r#"<tr>
+            <td title="{}"><strong>{}</strong></td>
+            <td><span class="badge badge-info">{}</span></td>
+            <td><span class="badge badge-{}">{}</span></td>
+            <td>{} {}</td>
+            <td>{}</td>
+        </tr>"#,
+        escape_html(&repo.display_path(root)),
+        escape_html(&crate::shorten_path(&repo.display_path(root), max_path_len)),
+        escape_html(repo.branch.as_deref().unwrap_or("N/A")),
+        status_class,
+        status_text,
+        ahead_badge,
+        behind_badge,
+        last_commit
+    )
+}
+
+fn table_html<'a>(repos: impl Iterator<Item = &'a RepoStatus>, root: Option<&Path>, max_path_len: Option<usize>) -> String {
+    let rows: String = repos.map(|repo| table_row(repo, root, max_path_len)).collect();
+    format!(
+        r#"<table>
             <thead>
                 <tr>
                     <th>Repository</th>
@@ -245,18 +519,162 @@ fn generate_html(repos: &[RepoStatus]) -> Result<String> {
             <tbody>
                 {}
             </tbody>
-        </table>
-        <div class="footer">
-            Generated by <strong>git-nexus</strong> · A blazing fast multi-repository scanner
-        </div>
-    </div>
-</body>
-</html>"#,
-        now,
-        now,
-        repos.len(),
-        repos.iter().filter(|r| r.is_clean).count(),
-        repos.iter().filter(|r| !r.is_clean).count(),
+        </table>"#,
         rows
-    ))
+    )
+}
+
+/// One anchored section per group, in group order, behind a table of
+/// contents with per-group counts. Plain links, so large reports stay
+/// navigable without any script.
+fn grouped_html(repos: &[RepoStatus], root: Option<&Path>, group: HtmlGroup, options: &HtmlOptions) -> String {
+    let mut groups: BTreeMap<(u8, String), Vec<&RepoStatus>> = BTreeMap::new();
+    for repo in repos {
+        groups.entry(group_key(repo, group, &options.scan_root)).or_default().push(repo);
+    }
+
+    let mut toc = String::new();
+    let mut sections = String::new();
+    for (index, ((_, label), members)) in groups.iter().enumerate() {
+        let label = escape_html(label);
+        toc.push_str(&format!(
+            r##"<li><a href="#group-{}">{}</a> <span class="badge badge-info">{}</span></li>"##,
+            index, label, members.len()
+        ));
+        sections.push_str(&format!(
+            r##"<section class="group" id="group-{}">
+            <h2>{} <small>({})</small> <a class="top-link" href="#toc">↑ top</a></h2>
+            {}
+        </section>"##,
+            index,
+            label,
+            members.len(),
+            table_html(members.iter().copied(), root, options.max_path_len)
+        ));
+    }
+
+    format!(
+        r#"<nav class="toc" id="toc">
+            <h2>Contents</h2>
+            <ul>{}</ul>
+        </nav>
+        {}"#,
+        toc, sections
+    )
+}
+
+/// Sort rank and heading of the group `repo` belongs to.
+fn group_key(repo: &RepoStatus, group: HtmlGroup, scan_root: &Path) -> (u8, String) {
+    match group {
+        HtmlGroup::Status => {
+            let rank = match repo.status_label() {
+                "DIRTY" => 0,
+                "ERROR" => 1,
+                _ => 2,
+            };
+            (rank, repo.status_label().to_string())
+        }
+        HtmlGroup::Directory => {
+            let relative = repo.path.strip_prefix(scan_root).unwrap_or(&repo.path);
+            let first = relative
+                .components()
+                .next()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .unwrap_or_else(|| ".".to_string());
+            (0, first)
+        }
+    }
+}
+
+/// Commit-recency buckets for the activity section, freshest first, with
+/// their colors from green (fresh) to gray (stale or unknown).
+const ACTIVITY_BUCKETS: [(&str, &str); 5] = [
+    ("Today", "#2da44e"),
+    ("This week", "#7dcf8e"),
+    ("This month", "#c6e9cc"),
+    ("Older", "#bcc3cb"),
+    ("Unknown", "#e9ecef"),
+];
+
+fn activity_bucket(repo: &RepoStatus) -> usize {
+    let Some(commit) = &repo.last_commit else {
+        return 4;
+    };
+    match (Utc::now().timestamp() - commit.seconds) / 86_400 {
+        ..1 => 0,
+        1..7 => 1,
+        7..30 => 2,
+        _ => 3,
+    }
+}
+
+/// A stacked bar of how many repositories fall into each recency bucket,
+/// then one colored tile per repository (hover for its path). Plain styled
+/// divs, no scripts.
+fn activity_html(repos: &[RepoStatus], root: Option<&Path>) -> String {
+    let buckets: Vec<usize> = repos.iter().map(activity_bucket).collect();
+    let mut counts = [0usize; ACTIVITY_BUCKETS.len()];
+    for &bucket in &buckets {
+        counts[bucket] += 1;
+    }
+
+    let mut bar = String::new();
+    let mut legend = String::new();
+    for (&(label, color), &count) in ACTIVITY_BUCKETS.iter().zip(&counts) {
+        if count > 0 {
+            let width = count as f64 * 100.0 / repos.len() as f64;
+            bar.push_str(&format!(
+                r#"<div style="width: {:.2}%; background: {};" title="{}: {}"></div>"#,
+                width, color, label, count
+            ));
+        }
+        legend.push_str(&format!(
+            r#"<span><span class="legend-swatch" style="background: {};"></span>{} ({})</span>"#,
+            color, label, count
+        ));
+    }
+
+    let mut tiles = String::new();
+    for (repo, &bucket) in repos.iter().zip(&buckets) {
+        let (label, color) = ACTIVITY_BUCKETS[bucket];
+        tiles.push_str(&format!(
+            r#"<span class="tile" style="background: {};" title="{} ({})"></span>"#,
+            color,
+            escape_html(&repo.display_path(root)),
+            label.to_lowercase()
+        ));
+    }
+
+    format!(
+        r#"<div class="activity">
+            <h2>Activity by last commit</h2>
+            <div class="activity-bar">{}</div>
+            <div class="activity-legend">{}</div>
+            <div class="activity-tiles">{}</div>
+        </div>"#,
+        bar, legend, tiles
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_row_escapes_repository_data() {
+        let mut repo = RepoStatus::failed(PathBuf::from("a&b"), "<script>alert(1)</script>".to_string());
+        repo.branch = Some("\"><img src=x>".to_string());
+
+        let row = table_row(&repo, None, None);
+        assert!(!row.contains("<script>") && !row.contains("<img"), "{}", row);
+        assert!(row.contains("&lt;script&gt;") && row.contains("a&amp;b"), "{}", row);
+    }
 }
@@ -0,0 +1,96 @@
+use serde::Serialize;
+
+use crate::github::GitHubInfo;
+use crate::RepoStatus;
+
+/// One table row per repository: every nested or optional field of
+/// `RepoStatus` becomes its own column, always present (null when unknown),
+/// so the output imports cleanly into Nushell, DataFrames or spreadsheets.
+#[derive(Debug, Serialize)]
+pub struct FlatRepo {
+    path: String,
+    status: &'static str,
+    is_clean: bool,
+    ahead: usize,
+    behind: usize,
+    branch: Option<String>,
+    important: bool,
+    labels: String,
+    is_shallow: bool,
+    unborn_branch: bool,
+    has_remote: bool,
+    repo_state: Option<crate::operation::Operation>,
+    stash_count: Option<usize>,
+    modified_count: Option<usize>,
+    untracked_count: Option<usize>,
+    last_commit_hash: Option<String>,
+    last_commit_author: Option<String>,
+    last_commit_email: Option<String>,
+    last_commit_message: Option<String>,
+    last_commit_timestamp: Option<String>,
+    last_commit_seconds: Option<i64>,
+    latest_tag: Option<String>,
+    remotes: Option<String>,
+    hooks: Option<String>,
+    github_status: Option<&'static str>,
+    github_open_issues: Option<usize>,
+    github_open_prs: Option<usize>,
+    health: Option<u8>,
+    top_suggestion: Option<String>,
+    error: Option<String>,
+}
+
+pub fn flatten(repos: &[RepoStatus]) -> Vec<FlatRepo> {
+    repos.iter().map(flatten_one).collect()
+}
+
+fn flatten_one(repo: &RepoStatus) -> FlatRepo {
+    let commit = repo.last_commit.as_ref();
+    let (github_status, github_open_issues, github_open_prs) = match &repo.github {
+        Some(GitHubInfo::Fetched { open_issues, open_prs }) => (Some("fetched"), Some(*open_issues), Some(*open_prs)),
+        Some(GitHubInfo::RateLimited { .. }) => (Some("rate_limited"), None, None),
+        Some(GitHubInfo::NotFound) => (Some("not_found"), None, None),
+        Some(GitHubInfo::Failed { .. }) => (Some("failed"), None, None),
+        None => (None, None, None),
+    };
+
+    FlatRepo {
+        path: repo.path.display().to_string(),
+        status: repo.status_label(),
+        is_clean: repo.is_clean,
+        ahead: repo.ahead,
+        behind: repo.behind,
+        branch: repo.branch.clone(),
+        important: repo.important,
+        labels: repo.labels.join(","),
+        is_shallow: repo.is_shallow,
+        unborn_branch: repo.unborn_branch,
+        has_remote: repo.has_remote,
+        repo_state: repo.repo_state,
+        stash_count: repo.stash_count,
+        modified_count: repo.modified_count,
+        untracked_count: repo.untracked_count,
+        last_commit_hash: commit.map(|c| c.hash.clone()),
+        last_commit_author: commit.map(|c| c.author.clone()),
+        last_commit_email: commit.map(|c| c.email.clone()),
+        last_commit_message: commit.map(|c| c.message.clone()),
+        last_commit_timestamp: commit.map(|c| c.timestamp.clone()),
+        last_commit_seconds: commit.map(|c| c.seconds),
+        latest_tag: repo.latest_tag.clone(),
+        remotes: repo
+            .remotes
+            .as_ref()
+            .map(|remotes| remotes.iter().map(|r| r.name.as_str()).collect::<Vec<_>>().join(",")),
+        hooks: repo.hooks.as_ref().map(|h| h.active_hooks().join(",")),
+        github_status,
+        github_open_issues,
+        github_open_prs,
+        health: repo.health,
+        top_suggestion: repo
+            .suggestions
+            .as_ref()
+            .and_then(|s| s.first())
+            .map(|s| s.message.clone()),
+        error: repo.error.clone(),
+    }
+}
@@ -1,38 +1,157 @@
 use anyhow::Result;
 use git2::Repository;
-use serde::Deserialize;
+use indicatif::{ProgressBar, ProgressStyle};
+use rayon::prelude::*;
+use serde::Serialize;
 use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
 
-#[derive(Debug, Deserialize)]
-struct GitHubIssue {
-    number: u32,
-    title: String,
-    state: String,
+use crate::cache::GitHubCache;
+use crate::{glyphs, RepoStatus};
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GitHubInfo {
+    Fetched { open_issues: usize, open_prs: usize },
+    RateLimited { reset_in_secs: Option<u64> },
+    /// GitHub answered 404: a private repository without a token that can
+    /// see it, or one that was deleted or renamed.
+    NotFound,
+    Failed { reason: String },
 }
 
-#[derive(Debug, Deserialize)]
-struct GitHubPR {
-    number: u32,
-    title: String,
-    state: String,
+/// How the GitHub lookups of one scan went, for the note after the listing.
+#[derive(Debug, Default)]
+pub struct FetchSummary {
+    pub fetched: usize,
+    pub rate_limited: usize,
+    pub not_found: usize,
+    pub failed: usize,
 }
 
-pub struct GitHubInfo {
-    pub open_issues: usize,
-    pub open_prs: usize,
+impl FetchSummary {
+    fn from_repos(repos: &[RepoStatus]) -> Self {
+        let mut summary = Self::default();
+        for info in repos.iter().filter_map(|r| r.github.as_ref()) {
+            match info {
+                GitHubInfo::Fetched { .. } => summary.fetched += 1,
+                GitHubInfo::RateLimited { .. } => summary.rate_limited += 1,
+                GitHubInfo::NotFound => summary.not_found += 1,
+                GitHubInfo::Failed { .. } => summary.failed += 1,
+            }
+        }
+        summary
+    }
+
+    /// Say on stderr how many lookups were skipped and why, so missing
+    /// counts aren't read as "no issues". Silent when everything worked.
+    pub fn report(&self) {
+        let skipped = self.rate_limited + self.not_found + self.failed;
+        if skipped == 0 {
+            return;
+        }
+
+        let reasons: Vec<String> = [
+            (self.rate_limited, "rate-limited"),
+            (self.not_found, "not found"),
+            (self.failed, "failed"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, reason)| format!("{} {}", count, reason))
+        .collect();
+        eprintln!(
+            "{} GitHub data: {} fetched, {} skipped ({})",
+            glyphs::WARNING,
+            self.fetched,
+            skipped,
+            reasons.join(", ")
+        );
+        if self.rate_limited + self.not_found > 0 {
+            eprintln!(
+                "  Set a token in the [github] config section or GITHUB_TOKEN, or run `gh auth login`, \
+                 to raise the rate limit and see private repositories."
+            );
+        }
+    }
+}
+
+pub struct FetchOptions<'a> {
+    pub token: Option<&'a str>,
+    /// Seconds a cached result stays fresh.
+    pub cache_ttl: u64,
+    /// Ignore cached results and hit the API for every repository.
+    pub refresh: bool,
+    pub show_progress: bool,
+}
+
+/// Fetch GitHub data for all repositories in parallel. The HTTP calls block,
+/// so a progress bar is drawn on stderr unless `show_progress` is off.
+pub fn fetch_all(repos: &mut [RepoStatus], options: &FetchOptions) -> FetchSummary {
+    let cache = Mutex::new(GitHubCache::load());
+
+    let progress = if options.show_progress {
+        ProgressBar::new(repos.len() as u64)
+    } else {
+        ProgressBar::hidden()
+    };
+    progress.set_style(
+        ProgressStyle::with_template("{spinner:.cyan} fetching GitHub data {pos}/{len}")
+            .expect("valid progress template"),
+    );
+    progress.enable_steady_tick(Duration::from_millis(100));
+
+    repos.par_iter_mut().for_each(|repo| {
+        if repo.error.is_none() {
+            repo.github = get_github_info(&repo.path, options, &cache)
+                .unwrap_or_else(|e| Some(GitHubInfo::Failed { reason: e.to_string() }));
+        }
+        progress.inc(1);
+    });
+
+    progress.finish_and_clear();
+
+    if let Ok(cache) = cache.into_inner()
+        && let Err(e) = cache.save()
+    {
+        eprintln!("{} Could not write GitHub cache: {}", glyphs::WARNING, e);
+    }
+
+    FetchSummary::from_repos(repos)
 }
 
-pub fn get_github_info(repo_path: &Path, token: Option<&str>) -> Result<Option<GitHubInfo>> {
+pub fn get_github_info(
+    repo_path: &Path,
+    options: &FetchOptions,
+    cache: &Mutex<GitHubCache>,
+) -> Result<Option<GitHubInfo>> {
     let repo = Repository::open(repo_path)?;
     
     // Try to get remote URL
     let remote = repo.find_remote("origin").ok();
     let url = remote.and_then(|r| r.url().map(String::from));
     
-    if let Some(url) = url {
-        if let Some((owner, repo_name)) = parse_github_url(&url) {
-            return fetch_github_data(&owner, &repo_name, token);
+    if let Some(url) = url
+        && let Some((owner, repo_name)) = parse_github_url(&url)
+    {
+        let key = format!("{}/{}", owner, repo_name);
+
+        if !options.refresh
+            && let Some((open_issues, open_prs)) = cache.lock().ok().and_then(|c| c.get(&key, options.cache_ttl))
+        {
+            log::debug!("GitHub cache hit for {}", key);
+            return Ok(Some(GitHubInfo::Fetched { open_issues, open_prs }));
+        }
+
+        log::debug!("fetching GitHub data for {}", key);
+        let info = fetch_github_data(&owner, &repo_name, options.token)?;
+        if let Some(GitHubInfo::Fetched { open_issues, open_prs }) = info
+            && let Ok(mut cache) = cache.lock()
+        {
+            cache.insert(key, open_issues, open_prs);
         }
+        return Ok(info);
     }
     
     Ok(None)
@@ -58,79 +177,166 @@ fn parse_github_url(url: &str) -> Option<(String, String)> {
     None
 }
 
+/// Attempts per request, including the first one.
+const MAX_ATTEMPTS: u32 = 4;
+
+/// Longest rate-limit wait worth sleeping through; anything longer is
+/// reported as rate limited instead of stalling the scan.
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(10);
+
+enum RequestError {
+    RateLimited(Option<Duration>),
+    NotFound,
+    Failed(String),
+}
+
 fn fetch_github_data(owner: &str, repo: &str, token: Option<&str>) -> Result<Option<GitHubInfo>> {
     let client = reqwest::blocking::Client::new();
     let base_url = "https://api.github.com";
-    
-    let mut builder = client.get(format!("{}/repos/{}/{}", base_url, owner, repo));
-    
-    if let Some(token) = token {
-        builder = builder.header("Authorization", format!("token {}", token));
+
+    let counts = get_with_retry(&client, &format!("{}/repos/{}/{}", base_url, owner, repo), token)
+        .and_then(|_| {
+            let issues_url = format!("{}/repos/{}/{}/issues?state=open&per_page=1", base_url, owner, repo);
+            let prs_url = format!("{}/repos/{}/{}/pulls?state=open&per_page=1", base_url, owner, repo);
+            Ok((
+                count_open(&client, &issues_url, token)?,
+                count_open(&client, &prs_url, token)?,
+            ))
+        });
+
+    let info = match counts {
+        Ok((open_issues, open_prs)) => GitHubInfo::Fetched { open_issues, open_prs },
+        Err(RequestError::RateLimited(wait)) => GitHubInfo::RateLimited {
+            reset_in_secs: wait.map(|w| w.as_secs()),
+        },
+        Err(RequestError::NotFound) => GitHubInfo::NotFound,
+        Err(RequestError::Failed(reason)) => GitHubInfo::Failed { reason },
+    };
+
+    Ok(Some(info))
+}
+
+/// Count open items behind a `per_page=1` listing. GitHub only sends a
+/// `link` header when there is more than one page, so a missing header
+/// means the body holds the whole (0 or 1 item) result.
+fn count_open(client: &reqwest::blocking::Client, url: &str, token: Option<&str>) -> Result<usize, RequestError> {
+    let response = get_with_retry(client, url, token)?;
+
+    // With one item per page, the last page number is the count.
+    if let Some(link_header) = response.headers().get("link") {
+        let link = link_header.to_str().unwrap_or("");
+        return parse_link_header(link).ok_or_else(|| RequestError::Failed(format!("unexpected Link header: {}", link)));
     }
-    
-    builder = builder.header("User-Agent", "git-nexus");
-    
-    let response = builder.send();
-    
-    if response.is_err() {
-        return Ok(None); // GitHub API might be unreachable
+
+    response
+        .json::<Vec<serde_json::Value>>()
+        .map(|items| items.len())
+        .map_err(|e| RequestError::Failed(e.to_string()))
+}
+
+/// GET with bounded retries: network errors and 5xx responses back off
+/// exponentially, rate limits wait for `Retry-After`/`x-ratelimit-reset`
+/// when that is short enough.
+fn get_with_retry(
+    client: &reqwest::blocking::Client,
+    url: &str,
+    token: Option<&str>,
+) -> Result<reqwest::blocking::Response, RequestError> {
+    let mut backoff = Duration::from_millis(500);
+    let mut attempt = 1;
+
+    loop {
+        let mut builder = client.get(url).header("User-Agent", "git-nexus");
+        if let Some(token) = token {
+            builder = builder.header("Authorization", format!("token {}", token));
+        }
+
+        let last_attempt = attempt >= MAX_ATTEMPTS;
+        log::trace!("GET {} (attempt {})", url, attempt);
+        let wait = match builder.send() {
+            Ok(response) if response.status().is_success() => return Ok(response),
+            Ok(response) if is_rate_limited(&response) => {
+                let wait = rate_limit_wait(&response);
+                log::warn!("rate limited on {} (reset in {:?})", url, wait);
+                match wait {
+                    Some(wait) if !last_attempt && wait <= MAX_RATE_LIMIT_WAIT => wait,
+                    _ => return Err(RequestError::RateLimited(wait)),
+                }
+            }
+            Ok(response) if response.status() == reqwest::StatusCode::NOT_FOUND => return Err(RequestError::NotFound),
+            Ok(response) if response.status().is_server_error() && !last_attempt => backoff,
+            Ok(response) => return Err(RequestError::Failed(format!("HTTP {}", response.status()))),
+            Err(_) if !last_attempt => backoff,
+            Err(e) => return Err(RequestError::Failed(e.to_string())),
+        };
+
+        log::debug!("retrying {} in {:?}", url, wait);
+        std::thread::sleep(wait);
+        backoff *= 2;
+        attempt += 1;
     }
-    
-    // Get issues count
-    let issues_url = format!("{}/repos/{}/{}/issues?state=open&per_page=1", base_url, owner, repo);
-    let mut issues_builder = client.get(&issues_url).header("User-Agent", "git-nexus");
-    if let Some(token) = token {
-        issues_builder = issues_builder.header("Authorization", format!("token {}", token));
+}
+
+fn is_rate_limited(response: &reqwest::blocking::Response) -> bool {
+    let status = response.status();
+    if status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        return true;
     }
-    
-    let issues_response = issues_builder.send();
-    let open_issues = if let Ok(resp) = issues_response {
-        if let Some(link_header) = resp.headers().get("link") {
-            parse_link_header(link_header.to_str().unwrap_or(""))
-        } else {
-            0
-        }
-    } else {
-        0
+
+    let headers = response.headers();
+    status == reqwest::StatusCode::FORBIDDEN
+        && (headers.contains_key("retry-after")
+            || headers.get("x-ratelimit-remaining").is_some_and(|v| v == "0"))
+}
+
+fn rate_limit_wait(response: &reqwest::blocking::Response) -> Option<Duration> {
+    let headers = response.headers();
+    let header_secs = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok())
     };
-    
-    // Get PRs count
-    let prs_url = format!("{}/repos/{}/{}/pulls?state=open&per_page=1", base_url, owner, repo);
-    let mut prs_builder = client.get(&prs_url).header("User-Agent", "git-nexus");
-    if let Some(token) = token {
-        prs_builder = prs_builder.header("Authorization", format!("token {}", token));
+
+    if let Some(secs) = header_secs("retry-after") {
+        return Some(Duration::from_secs(secs));
     }
-    
-    let prs_response = prs_builder.send();
-    let open_prs = if let Ok(resp) = prs_response {
-        if let Some(link_header) = resp.headers().get("link") {
-            parse_link_header(link_header.to_str().unwrap_or(""))
-        } else {
-            0
-        }
-    } else {
-        0
-    };
-    
-    Ok(Some(GitHubInfo {
-        open_issues,
-        open_prs,
-    }))
+
+    let reset = header_secs("x-ratelimit-reset")?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_secs();
+    Some(Duration::from_secs(reset.saturating_sub(now)))
 }
 
-fn parse_link_header(link: &str) -> usize {
-    // Parse GitHub link header to get total count
-    // Example: <url?page=2>; rel="next", <url?page=10>; rel="last"
-    for part in link.split(',') {
-        if part.contains("rel=\"last\"") {
-            if let Some(page_str) = part.split("page=").nth(1) {
-                if let Some(num_str) = page_str.split('>').next() {
-                    if let Ok(num) = num_str.parse::<usize>() {
-                        return num;
-                    }
-                }
-            }
-        }
+/// The `page` query parameter of the `rel="last"` link, e.g. 10 in
+/// `<https://api.github.com/...?per_page=1&page=10>; rel="last"`.
+fn parse_link_header(link: &str) -> Option<usize> {
+    let last = link.split(',').find(|part| part.contains("rel=\"last\""))?;
+    let url = last.split_once('<')?.1.split_once('>')?.0;
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("page="))
+        .and_then(|page| page.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_the_last_page_from_a_github_link_header() {
+        let link = "<https://api.github.com/repositories/1300192/issues?state=open&per_page=1&page=2>; rel=\"next\", \
+                    <https://api.github.com/repositories/1300192/issues?state=open&per_page=1&page=57>; rel=\"last\"";
+        assert_eq!(parse_link_header(link), Some(57));
+    }
+
+    #[test]
+    fn rejects_link_headers_without_a_last_page() {
+        let first_page_only = "<https://api.github.com/repositories/1300192/issues?state=open&per_page=1&page=1>; rel=\"first\"";
+        assert_eq!(parse_link_header(first_page_only), None);
+        assert_eq!(parse_link_header("garbage"), None);
     }
-    0
 }
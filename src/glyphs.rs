@@ -0,0 +1,69 @@
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::IconsConfig;
+
+static ASCII: AtomicBool = AtomicBool::new(false);
+static ICONS: OnceLock<IconsConfig> = OnceLock::new();
+
+/// Switch every glyph to its plain-ASCII fallback (`--ascii` / `--no-emoji`).
+pub fn set_ascii(ascii: bool) {
+    ASCII.store(ascii, Ordering::Relaxed);
+}
+
+/// Install the `[display.icons]` overrides. Only the first call counts.
+pub fn set_icons(icons: IconsConfig) {
+    let _ = ICONS.set(icons);
+}
+
+/// The user's replacement for a symbol or label, if `[display.icons]` sets
+/// one, else `default`.
+pub fn icon(pick: impl Fn(&IconsConfig) -> &Option<String>, default: &str) -> &str {
+    ICONS.get().and_then(|icons| pick(icons).as_deref()).unwrap_or(default)
+}
+
+/// A status symbol with an ASCII fallback for terminals and logs that can't
+/// render emoji or box-drawing characters.
+#[derive(Debug, Clone, Copy)]
+pub struct Glyph {
+    emoji: &'static str,
+    ascii: &'static str,
+}
+
+impl Glyph {
+    pub fn get(&self) -> &'static str {
+        if ASCII.load(Ordering::Relaxed) {
+            self.ascii
+        } else {
+            self.emoji
+        }
+    }
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+pub const REPO: Glyph = Glyph { emoji: "📁", ascii: "*" };
+pub const SEARCH: Glyph = Glyph { emoji: "🔍", ascii: ">" };
+pub const FOUND: Glyph = Glyph { emoji: "✓", ascii: "+" };
+pub const DONE: Glyph = Glyph { emoji: "✅", ascii: "[ok]" };
+pub const WARNING: Glyph = Glyph { emoji: "⚠", ascii: "!" };
+pub const AHEAD: Glyph = Glyph { emoji: "↑", ascii: "^" };
+pub const BEHIND: Glyph = Glyph { emoji: "↓", ascii: "v" };
+pub const STASH: Glyph = Glyph { emoji: "📦", ascii: "s" };
+pub const HOOKS: Glyph = Glyph { emoji: "🪝", ascii: "h" };
+pub const TREE: Glyph = Glyph { emoji: "└─", ascii: "`-" };
+pub const SEPARATOR: Glyph = Glyph { emoji: "·", ascii: "|" };
+pub const WATCH: Glyph = Glyph { emoji: "👁️ ", ascii: ">>" };
+pub const IMPORTANT: Glyph = Glyph { emoji: "★", ascii: "#" };
+pub const PAUSED: Glyph = Glyph { emoji: "⏸", ascii: "||" };
+pub const SUGGEST: Glyph = Glyph { emoji: "💡", ascii: "?" };
+pub const RUN: Glyph = Glyph { emoji: "▶", ascii: "$" };
+pub const SHALLOW: Glyph = Glyph { emoji: "✂", ascii: "%" };
+pub const RESCAN: Glyph = Glyph { emoji: "🔄", ascii: "~>" };
+pub const ARROW: Glyph = Glyph { emoji: "→", ascii: "->" };
+pub const ELLIPSIS: Glyph = Glyph { emoji: "…", ascii: "..." };
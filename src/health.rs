@@ -0,0 +1,167 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::RepoStatus;
+
+/// Score a repository from 0 (needs attention) to 100 (nothing to do).
+///
+/// Deductions: uncommitted changes 20, detached HEAD 10, 5 per commit behind
+/// (up to 25), 2 per commit ahead (up to 10) and, when verbose data was
+/// collected, 5 per stash (up to 15). A repository that could not be analyzed
+/// scores 0.
+pub fn calculate_health_score(repo: &RepoStatus) -> u8 {
+    Inputs {
+        failed: repo.error.is_some(),
+        is_clean: repo.is_clean,
+        detached: repo.is_detached(),
+        ahead: repo.ahead,
+        behind: repo.behind,
+        stashes: repo.stash_count.unwrap_or(0),
+    }
+    .score()
+}
+
+/// What the score is computed from, so a saved scan can be scored the same
+/// way as a live one.
+struct Inputs {
+    failed: bool,
+    is_clean: bool,
+    detached: bool,
+    ahead: usize,
+    behind: usize,
+    stashes: usize,
+}
+
+impl Inputs {
+    fn score(&self) -> u8 {
+        if self.failed {
+            return 0;
+        }
+
+        let mut penalty = 0;
+        if !self.is_clean {
+            penalty += 20;
+        }
+        if self.detached {
+            penalty += 10;
+        }
+        penalty += (self.behind * 5).min(25);
+        penalty += (self.ahead * 2).min(10);
+        penalty += (self.stashes * 5).min(15);
+
+        100usize.saturating_sub(penalty) as u8
+    }
+}
+
+/// A repository as written by an earlier `--json` scan; only the fields the
+/// score depends on are read.
+#[derive(Deserialize)]
+struct SavedRepo {
+    path: PathBuf,
+    is_clean: bool,
+    ahead: usize,
+    behind: usize,
+    branch: Option<String>,
+    stash_count: Option<usize>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SavedScan {
+    Repos(Vec<SavedRepo>),
+    Report { repos: Vec<SavedRepo> },
+}
+
+/// Health scores from a `--baseline` file, by repository path.
+pub struct Baseline {
+    scores: HashMap<PathBuf, u8>,
+}
+
+impl Baseline {
+    /// Read a `--json` scan, bare or in the `--show-errors` envelope, and
+    /// score it with today's rules rather than trusting any stored `health`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("cannot read baseline {}", path.display()))?;
+        let scan: SavedScan = serde_json::from_str(&content)
+            .with_context(|| format!("{} is not a git-nexus --json scan", path.display()))?;
+        let repos = match scan {
+            SavedScan::Repos(repos) | SavedScan::Report { repos } => repos,
+        };
+        let scores = repos
+            .into_iter()
+            .map(|repo| {
+                let score = Inputs {
+                    failed: repo.error.is_some(),
+                    is_clean: repo.is_clean,
+                    detached: repo.branch.as_deref().is_some_and(|b| b.starts_with("detached@")),
+                    ahead: repo.ahead,
+                    behind: repo.behind,
+                    stashes: repo.stash_count.unwrap_or(0),
+                }
+                .score();
+                (canonical(&repo.path), score)
+            })
+            .collect();
+        Ok(Self { scores })
+    }
+
+    /// Average health then and now, and the change for every repository
+    /// present in both scans, in the order of `repos`.
+    pub fn compare(&self, repos: &[RepoStatus], root: Option<&Path>) -> Comparison {
+        let mut changes = Vec::new();
+        let mut now = Vec::with_capacity(repos.len());
+        for repo in repos {
+            let score = repo.health.unwrap_or_else(|| calculate_health_score(repo));
+            now.push(score);
+            if let Some(&before) = self.scores.get(&canonical(&repo.path)) {
+                changes.push((repo.display_path(root), before, score));
+            }
+        }
+        Comparison {
+            before: average(self.scores.values().copied()),
+            after: average(now.into_iter()),
+            changes,
+        }
+    }
+}
+
+pub struct Comparison {
+    pub before: Option<u8>,
+    pub after: Option<u8>,
+    /// Repositories scanned both times, with their score then and now.
+    pub changes: Vec<(String, u8, u8)>,
+}
+
+/// Paths in a saved scan are as they were given on the command line; resolve
+/// them when they still exist so `.` and an absolute path compare equal.
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Rounded mean of `scores`, None when there are none.
+pub fn average(scores: impl Iterator<Item = u8>) -> Option<u8> {
+    let (sum, count) = scores.fold((0usize, 0usize), |(sum, count), s| (sum + s as usize, count + 1));
+    (count > 0).then(|| ((sum + count / 2) / count) as u8)
+}
+
+/// Health bounds from `--min-health` / `--max-health`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HealthRange {
+    /// Keep repositories scoring below this (they fall short of the minimum).
+    pub min: Option<u8>,
+    /// Keep repositories scoring above this.
+    pub max: Option<u8>,
+}
+
+impl HealthRange {
+    pub fn is_active(&self) -> bool {
+        self.min.is_some() || self.max.is_some()
+    }
+
+    pub fn matches(&self, score: u8) -> bool {
+        self.min.is_none_or(|min| score < min) && self.max.is_none_or(|max| score > max)
+    }
+}
@@ -0,0 +1,273 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Drop-in ignore file looked up in every directory visited by the walk.
+pub const IGNORE_FILE_NAME: &str = ".git-nexus-ignore";
+
+/// A set of gitignore-style patterns used to prune directories from the scan.
+///
+/// Patterns without a `/` match any single directory name (`node_modules`,
+/// `*.egg-info`). Patterns containing a `/` are anchored to the scan root and
+/// matched against the relative path (`vendor/*`, `archive/**/old`).
+/// `*` and `?` never cross a path separator; `**` does.
+#[derive(Debug, Clone, Default)]
+pub struct IgnorePatterns {
+    patterns: Vec<Pattern>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Pattern {
+    glob: Vec<char>,
+    anchored: bool,
+}
+
+impl IgnorePatterns {
+    pub fn new(patterns: &[String]) -> Self {
+        let mut ignore = Self::default();
+        for pattern in patterns {
+            ignore.add(pattern);
+        }
+        ignore
+    }
+
+    /// Load patterns from a file, one per line. Blank lines and lines starting
+    /// with `#` are skipped.
+    pub fn load_from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .with_context(|| format!("failed to read ignore file {}", path.display()))?;
+
+        let mut ignore = Self::default();
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            ignore.add(line);
+        }
+        Ok(ignore)
+    }
+
+    pub fn add(&mut self, pattern: &str) {
+        let trimmed = pattern.trim_end_matches('/');
+        if trimmed.is_empty() {
+            return;
+        }
+
+        let anchored = trimmed.contains('/');
+        let pattern = Pattern {
+            glob: trimmed.trim_start_matches('/').chars().collect(),
+            anchored,
+        };
+
+        if !self.patterns.contains(&pattern) {
+            self.patterns.push(pattern);
+        }
+    }
+
+    /// Union another pattern set into this one.
+    pub fn merge(&mut self, other: IgnorePatterns) {
+        for pattern in other.patterns {
+            if !self.patterns.contains(&pattern) {
+                self.patterns.push(pattern);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Check a directory, given by its path relative to the scan root.
+    pub fn is_ignored(&self, relative: &Path) -> bool {
+        let name: Vec<char> = match relative.file_name() {
+            Some(name) => name.to_string_lossy().chars().collect(),
+            None => return false,
+        };
+        let full: Vec<char> = relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+            .chars()
+            .collect();
+
+        self.patterns.iter().any(|p| {
+            if p.anchored {
+                glob_match(&p.glob, &full)
+            } else {
+                glob_match(&p.glob, &name)
+            }
+        })
+    }
+}
+
+impl IgnorePatterns {
+    /// Check a path relative to the scan root, or any of its ancestors below
+    /// the root. Used for inclusion lists, where `backend` selects every
+    /// repository under a `backend` directory.
+    pub fn matches_within(&self, relative: &Path) -> bool {
+        relative
+            .ancestors()
+            .take_while(|p| !p.as_os_str().is_empty())
+            .any(|p| self.is_ignored(p))
+    }
+}
+
+/// `.git-nexus-ignore` files discovered during the walk, loaded lazily and
+/// cached per directory.
+///
+/// A file's patterns apply to the descendants of the directory it lives in,
+/// relative to that directory. An empty file prunes its own directory.
+#[derive(Debug, Default)]
+pub struct IgnoreFiles {
+    loaded: HashMap<PathBuf, Option<IgnorePatterns>>,
+}
+
+impl IgnoreFiles {
+    pub fn is_ignored(&mut self, root: &Path, dir: &Path) -> bool {
+        if self.patterns_for(dir).is_some_and(|p| p.is_empty()) {
+            return true;
+        }
+
+        let mut ancestor = dir.parent();
+        while let Some(parent) = ancestor {
+            if !parent.starts_with(root) {
+                break;
+            }
+            let relative = dir.strip_prefix(parent).unwrap_or(dir);
+            if self.patterns_for(parent).is_some_and(|p| p.is_ignored(relative)) {
+                return true;
+            }
+            ancestor = parent.parent();
+        }
+
+        false
+    }
+
+    fn patterns_for(&mut self, dir: &Path) -> Option<&IgnorePatterns> {
+        self.loaded
+            .entry(dir.to_path_buf())
+            .or_insert_with(|| {
+                let file = dir.join(IGNORE_FILE_NAME);
+                if file.is_file() {
+                    IgnorePatterns::load_from_file(&file).ok()
+                } else {
+                    None
+                }
+            })
+            .as_ref()
+    }
+}
+
+/// Match a whole string against one glob, with the same rules as ignore
+/// patterns (`*`/`?` stop at `/`, `**` does not, `**/` matches zero or more
+/// directories). There are no character classes: `[` is literal.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    glob_match(&pattern, &text)
+}
+
+/// Iterative two-pointer matching, so patterns with many stars can't
+/// backtrack exponentially. Only the latest wildcard of each kind is kept as
+/// a retry point, since a later `*` (or `**/`, or `**`) can always take over
+/// what an earlier one would have matched.
+fn glob_match(pattern: &[char], text: &[char]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Wildcards passed so far, in pattern order: the kind, the pattern index
+    // after it and the text index its match currently ends at.
+    let mut retry: Vec<(Wildcard, usize, usize)> = Vec::new();
+
+    while p < pattern.len() || t < text.len() {
+        if let Some((wildcard, len)) = Wildcard::at(&pattern[p..]) {
+            p += len;
+            retry.retain(|&(kind, ..)| kind != wildcard);
+            retry.push((wildcard, p, t));
+            continue;
+        }
+        let matches = match (pattern.get(p), text.get(t)) {
+            (Some('?'), Some(&c)) => c != '/',
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        };
+        if matches {
+            p += 1;
+            t += 1;
+            continue;
+        }
+
+        // Mismatch: the latest wildcard that can take more text does, and
+        // the rest of the pattern is matched again from there.
+        loop {
+            let Some((kind, resume, end)) = retry.pop() else {
+                return false;
+            };
+            if let Some(next) = kind.grow(text, end) {
+                retry.push((kind, resume, next));
+                (p, t) = (resume, next);
+                break;
+            }
+        }
+    }
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Wildcard {
+    /// `*`: any characters but `/`
+    Star,
+    /// `**/`: zero or more whole directories
+    Dirs,
+    /// `**`: anything
+    Anything,
+}
+
+impl Wildcard {
+    /// The wildcard at the start of `pattern` and how many characters it takes.
+    fn at(pattern: &[char]) -> Option<(Self, usize)> {
+        match pattern {
+            ['*', '*', '/', ..] => Some((Self::Dirs, 3)),
+            ['*', '*', ..] => Some((Self::Anything, 2)),
+            ['*', ..] => Some((Self::Star, 1)),
+            _ => None,
+        }
+    }
+
+    /// Where a match ending at `end` ends after taking one more character,
+    /// or one more directory for `**/`, if it can.
+    fn grow(self, text: &[char], end: usize) -> Option<usize> {
+        match self {
+            Self::Star => text.get(end).filter(|&&c| c != '/').map(|_| end + 1),
+            Self::Dirs => text[end..].iter().position(|&c| c == '/').map(|slash| end + slash + 1),
+            Self::Anything => (end < text.len()).then_some(end + 1),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stars_stop_at_slashes_but_double_stars_do_not() {
+        assert!(glob_matches("*.git", "repo.git"));
+        assert!(!glob_matches("*.git", "team/repo.git"));
+        assert!(glob_matches("**/repo.git", "repo.git"));
+        assert!(glob_matches("**/repo.git", "team/sub/repo.git"));
+        assert!(glob_matches("https://github.com/*/repo-?", "https://github.com/me/repo-1"));
+        assert!(!glob_matches("https://github.com/*/repo-?", "https://github.com/me/x/repo-1"));
+        assert!(glob_matches("https://**", "https://github.com/me/repo.git"));
+        assert!(glob_matches("feature/**", "feature/a/b"));
+        assert!(!glob_matches("feature/**", "bugfix/a"));
+    }
+
+    #[test]
+    fn many_stars_do_not_backtrack_exponentially() {
+        let pattern = "*a".repeat(30) + "b";
+        let text = "a".repeat(100);
+        assert!(!glob_matches(&pattern, &text));
+        assert!(!glob_matches(&"**a".repeat(30), &format!("{}b", text)));
+    }
+}
@@ -0,0 +1,51 @@
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+/// Result of `git fsck` for one repository.
+#[derive(Debug, Clone, Serialize)]
+pub struct Integrity {
+    pub ok: bool,
+    /// Objects referenced somewhere but absent from the object database.
+    pub missing_objects: usize,
+    /// fsck's complaints, one per line (dangling objects are not reported).
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub problems: Vec<String>,
+}
+
+/// Run `git fsck` in `repo_path`. libgit2 has no equivalent connectivity
+/// check, so this shells out; it reads every object and is slow on large
+/// repositories, which is why it only runs under `--verify`.
+pub fn verify(repo_path: &Path) -> Integrity {
+    log::debug!("running git fsck in {}", repo_path.display());
+    let output = Command::new("git")
+        .args(["fsck", "--no-dangling", "--no-progress"])
+        .current_dir(repo_path)
+        .output();
+
+    let output = match output {
+        Ok(output) => output,
+        Err(e) => {
+            return Integrity {
+                ok: false,
+                missing_objects: 0,
+                problems: vec![format!("could not run git fsck: {}", e)],
+            };
+        }
+    };
+
+    let problems: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("Checking "))
+        .map(String::from)
+        .collect();
+    let missing_objects = problems.iter().filter(|line| line.starts_with("missing ")).count();
+
+    Integrity {
+        ok: output.status.success() && missing_objects == 0,
+        missing_objects,
+        problems,
+    }
+}
@@ -0,0 +1,22 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Turn the first Ctrl-C into a request to wrap up: scans stop starting on
+/// new repositories and show the ones already analyzed. A second Ctrl-C
+/// quits at once.
+pub fn install() {
+    let result = ctrlc::set_handler(|| {
+        if INTERRUPTED.swap(true, Ordering::Relaxed) {
+            std::process::exit(130);
+        }
+        eprintln!("\ninterrupted, finishing the repositories in progress (Ctrl-C again to quit)");
+    });
+    if let Err(e) = result {
+        log::warn!("cannot handle Ctrl-C: {}", e);
+    }
+}
+
+pub fn requested() -> bool {
+    INTERRUPTED.load(Ordering::Relaxed)
+}
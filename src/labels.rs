@@ -0,0 +1,37 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::ignore::IgnorePatterns;
+
+/// Labels from the config's `[labels]` table, which maps a path glob
+/// (relative to the scan root, same syntax as ignore patterns) to the labels
+/// of every repository at or below a matching directory.
+#[derive(Debug, Default)]
+pub struct Labels {
+    rules: Vec<(IgnorePatterns, Vec<String>)>,
+}
+
+impl Labels {
+    pub fn new(config: &BTreeMap<String, Vec<String>>) -> Self {
+        Self {
+            rules: config
+                .iter()
+                .map(|(glob, labels)| (IgnorePatterns::new(std::slice::from_ref(glob)), labels.clone()))
+                .collect(),
+        }
+    }
+
+    /// All labels for a repository, sorted and without duplicates.
+    pub fn for_path(&self, root: &Path, path: &Path) -> Vec<String> {
+        let relative = path.strip_prefix(root).unwrap_or(path);
+        let mut labels: Vec<String> = self
+            .rules
+            .iter()
+            .filter(|(pattern, _)| pattern.matches_within(relative))
+            .flat_map(|(_, labels)| labels.iter().cloned())
+            .collect();
+        labels.sort();
+        labels.dedup();
+        labels
+    }
+}
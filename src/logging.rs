@@ -0,0 +1,47 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use log::LevelFilter;
+use std::fs::File;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<LogLevel> for LevelFilter {
+    fn from(level: LogLevel) -> Self {
+        match level {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Set up diagnostic logging, separate from the regular output. Nothing is
+/// logged unless a level or a log file is given; a log file alone logs at
+/// `info`. Records go to the file if there is one, stderr otherwise.
+pub fn init(level: Option<LogLevel>, file: Option<&Path>) -> Result<()> {
+    if level.is_none() && file.is_none() {
+        return Ok(());
+    }
+
+    let mut builder = env_logger::Builder::new();
+    builder
+        .filter_level(level.unwrap_or(LogLevel::Info).into())
+        .format_timestamp_millis();
+
+    if let Some(path) = file {
+        let file = File::create(path).with_context(|| format!("failed to create log file {}", path.display()))?;
+        builder.target(env_logger::Target::Pipe(Box::new(file)));
+    }
+
+    builder.try_init().context("failed to initialize logging")
+}
@@ -1,51 +1,220 @@
+mod audit;
+mod bench;
+mod cache;
 mod config;
+mod dates;
+mod digest;
 mod export;
+mod flat;
 mod github;
+mod glyphs;
+mod health;
 mod hooks;
+mod ignore;
+mod integrity;
+mod interrupt;
+mod labels;
+mod logging;
+mod offline;
+mod operation;
+mod post_scan;
+mod prompt;
+mod pull;
+mod refresh;
+mod remote;
+mod shell;
+mod stash;
+mod submodules;
+mod suggest;
+mod summary;
 mod tui;
+mod update;
 mod watch;
+mod worktrees;
 
 use anyhow::Result;
-use chrono::{DateTime, Local};
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{ArgGroup, CommandFactory, Parser, Subcommand, ValueEnum};
 use colored::*;
 use config::Config;
 use git2::{Repository, StatusOptions};
+use ignore::{IgnoreFiles, IgnorePatterns};
 use rayon::prelude::*;
+use regex::Regex;
 use serde::Serialize;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
 use walkdir::WalkDir;
 
 #[derive(Parser)]
 #[command(name = "git-nexus")]
+#[command(group(ArgGroup::new("machine").args(["json", "output"]).multiple(true)))]
 #[command(version, about = "A blazing fast multi-repository scanner for developers", long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Option<Commands>,
 
-    #[arg(default_value = ".", help = "Root directory to scan for repositories")]
+    #[arg(default_value = ".", value_parser = expanded_path, help = "Root directory to scan for repositories")]
     path: PathBuf,
 
-    #[arg(short, long, help = "Maximum directory traversal depth")]
+    #[arg(short, long, help = "Maximum directory traversal depth (0 = treat PATH itself as the only repository)")]
     depth: Option<usize>,
 
     #[arg(short, long, help = "Output in JSON format")]
     json: bool,
 
-    #[arg(short = 'v', long, help = "Show verbose information")]
-    verbose: bool,
+    #[arg(long, value_enum, value_name = "LEVEL", help = "Log scan diagnostics at this level (to stderr, or --log-file)")]
+    log_level: Option<logging::LogLevel>,
+
+    #[arg(long, value_name = "PATH", value_parser = expanded_path, help = "Write diagnostic logs to a file (at info level unless --log-level is given)")]
+    log_file: Option<PathBuf>,
+
+    #[arg(long, value_enum, value_name = "FORMAT", help = "Machine-readable output format (--json is short for --output json)")]
+    output: Option<OutputFormat>,
+
+    #[arg(long, requires = "machine", help = "Flatten machine output into one table-friendly record per repository")]
+    flat: bool,
+
+    #[arg(long, requires = "machine", help = "Print JSON on a single line instead of pretty-printed")]
+    compact: bool,
+
+    #[arg(short = 'v', long, action = clap::ArgAction::Count, help = "Show more per repository: -v last commit, -vv stashes, file counts and hooks, -vvv remotes, tags and submodules")]
+    verbose: u8,
 
     #[arg(short, long, help = "Filter repositories by status")]
     filter: Option<StatusFilter>,
 
-    #[arg(short, long, value_enum, default_value = "path", help = "Sort repositories by field")]
-    sort: SortBy,
+    #[arg(short, long, value_enum, help = "Sort repositories by field [default: path]")]
+    sort: Option<SortBy>,
 
     #[arg(long, help = "Show git hooks information")]
     show_hooks: bool,
 
-    #[arg(long, help = "Show GitHub info (requires token in config)")]
+    #[arg(long, help = "Show GitHub info (token from config, GITHUB_TOKEN or gh CLI)")]
     show_github: bool,
+
+    #[arg(long, help = "Ignore cached GitHub data and fetch it again")]
+    refresh_github: bool,
+
+    #[arg(long, value_name = "PATH", value_parser = expanded_path, help = "Load additional ignore patterns from a file (one per line)")]
+    ignore_file: Option<PathBuf>,
+
+    #[arg(long, help = "Report repositories and directories that could not be scanned")]
+    show_errors: bool,
+
+    #[arg(long, help = "Exit with an error if any directory or repository could not be scanned")]
+    strict: bool,
+
+    #[arg(long, help = "Run the [[hooks]] of a .git-nexus.toml in the current directory (only the user config's hooks run otherwise)")]
+    trust_local_config: bool,
+
+    #[arg(long, help = "Analyze repositories one at a time instead of in parallel")]
+    sequential: bool,
+
+    #[arg(long, help = "Print each repository as soon as it is analyzed (unsorted)")]
+    stream: bool,
+
+    #[arg(long, alias = "no-emoji", help = "Use plain ASCII symbols instead of emoji")]
+    ascii: bool,
+
+    #[arg(long, env = "GIT_NEXUS_OFFLINE", value_parser = clap::builder::BoolishValueParser::new(), help = "Never use the network: no GitHub data, fetches, DNS checks or update checks")]
+    offline: bool,
+
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=100), help = "Show only repositories with a health score below N")]
+    min_health: Option<u8>,
+
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(0..=100), help = "Show only repositories with a health score above N")]
+    max_health: Option<u8>,
+
+    #[arg(long, value_name = "FILE", value_parser = expanded_path, help = "Compare workspace health with an earlier --json scan saved in FILE")]
+    baseline: Option<PathBuf>,
+
+    #[arg(long, help = "Suggest what to do in each repository, most urgent first")]
+    suggest: bool,
+
+    #[arg(long, help = "Like --suggest, plus slower checks: whether each origin host resolves, and an unset pull.rebase")]
+    audit: bool,
+
+    #[arg(long, value_name = "LABEL", help = "Only keep repositories with this label (repeatable, any match)")]
+    label: Vec<String>,
+
+    #[arg(long, value_enum, value_name = "FIELD", help = "Group the listing by a field")]
+    group_by: Option<GroupBy>,
+
+    #[arg(long, conflicts_with = "short", help = "Group the listing by branch and name each branch once, in its group header")]
+    collapse_branches: bool,
+
+    #[arg(long, value_name = "PATTERN", num_args = 1.., help = "Only keep repositories under these paths or matching these globs")]
+    only: Vec<String>,
+
+    #[arg(long, help = "Show repository paths relative to the scan root (terminal, CSV and HTML)")]
+    relative: bool,
+
+    #[arg(long, value_name = "RE", value_parser = Regex::new, help = "Show only repositories whose full path matches a regular expression")]
+    path_regex: Option<Regex>,
+
+    #[arg(long, help = "Check every repository's integrity with git fsck (slow)")]
+    verify: bool,
+
+    #[arg(long, value_name = "FORMAT", value_parser = dates::DateFormat::parse, help = "Commit date format: iso, relative or a strftime pattern (overrides date_format)")]
+    date: Option<dates::DateFormat>,
+
+    #[arg(long, value_name = "ZONE", value_parser = dates::Zone::parse, allow_hyphen_values = true, help = "Time zone for commit dates: local, utc or +HH:MM (overrides timezone)")]
+    timezone: Option<dates::Zone>,
+
+    #[arg(long, value_name = "PATTERN", help = "Show only repositories whose last commit author's name or email contains PATTERN")]
+    author: Option<String>,
+
+    #[arg(long, help = "Keep the whole last commit message and show its body in verbose output")]
+    full_message: bool,
+
+    #[arg(long, help = "List each repository's changed files, like git status -s")]
+    list_files: bool,
+
+    #[arg(long, conflicts_with_all = ["json", "output", "plain_paths", "only_issues_exit"], help = "Show each repository like git status -sb: a '## branch...upstream [ahead N, behind M]' line and its changed files")]
+    short: bool,
+
+    #[arg(long, help = "Count repositories with only untracked files as clean")]
+    ignore_untracked: bool,
+
+    #[arg(long, help = "List every status entry with its raw libgit2 flags, to see why a repository is dirty")]
+    explain_status: bool,
+
+    #[arg(long, help = "Only list repositories that need attention, and count the clean ones in one line")]
+    quiet_clean: bool,
+
+    #[arg(long, conflicts_with_all = ["json", "output", "stream"], help = "Print one line per repository needing attention and exit with status 1 if there is any (for git hooks)")]
+    only_issues_exit: bool,
+
+    #[arg(long, conflicts_with_all = ["json", "output", "stream", "only_issues_exit"], help = "Print only the paths of the matching repositories, one per line")]
+    plain_paths: bool,
+
+    #[arg(short = '0', long, conflicts_with_all = ["json", "output", "stream", "only_issues_exit"], help = "Like --plain-paths, but separate paths with NUL bytes (for xargs -0)")]
+    null: bool,
+
+    #[arg(long, value_name = "N", help = "Shorten repository paths longer than N characters in the middle, in the listing and HTML")]
+    max_path_len: Option<usize>,
+
+    #[arg(long, help = "Follow symlinked directories and report each repository once, however many paths reach it")]
+    dedupe: bool,
+
+    #[arg(long, requires = "dedupe", help = "With --dedupe, list the other paths each repository was found under")]
+    show_aliases: bool,
+
+    #[arg(long, value_name = "REF", help = "Show only submodules whose commit recorded in the enclosing repository changed between REF and HEAD")]
+    changed_since: Option<String>,
+
+    #[arg(long, value_enum, value_name = "FORMAT", requires = "export_to", help = "Also write an HTML or CSV report of this scan (collects verbose data)")]
+    export: Option<ExportFormat>,
+
+    #[arg(long, value_name = "PATH", value_parser = expanded_path, requires = "export", help = "Where --export writes the report")]
+    export_to: Option<PathBuf>,
+
+    #[arg(long, value_name = "MINUTES", help = "Fetch repositories not fetched in the last MINUTES before comparing with upstream")]
+    refresh_remotes_ttl: Option<u64>,
 }
 
 #[derive(Subcommand)]
@@ -54,22 +223,151 @@ enum Commands {
     Tui,
     
     /// Watch mode - continuously monitor for changes
-    Watch,
+    Watch {
+        /// Redraw a compact status table in place instead of scrolling
+        #[arg(long)]
+        dashboard: bool,
+
+        /// Shell command to run after a repository changes ({repo} is replaced by its path)
+        #[arg(long, value_name = "CMD")]
+        on_change: Option<String>,
+
+        /// Poll for changes instead of using OS notifications (for NFS/SMB mounts)
+        #[arg(long)]
+        poll: bool,
+
+        /// Seconds between polls, with --poll or when notifications are unavailable
+        #[arg(long, value_name = "SECS", default_value_t = 2, value_parser = clap::value_parser!(u64).range(1..))]
+        poll_interval: u64,
+    },
     
     /// Export to HTML or CSV
     Export {
         #[arg(value_enum)]
         format: ExportFormat,
         
-        #[arg(short, long)]
+        #[arg(short, long, value_parser = expanded_path)]
         output: PathBuf,
+
+        /// Add a commit-activity overview to the HTML report
+        #[arg(long)]
+        activity: bool,
+
+        /// Split the HTML report into sections with a table of contents
+        #[arg(long, value_enum, value_name = "FIELD")]
+        group: Option<export::HtmlGroup>,
+
+        /// Color scheme of the HTML report
+        #[arg(long, value_enum, default_value_t = export::HtmlTheme::Auto)]
+        theme: export::HtmlTheme,
+
+        /// Only export the totals (counts, ahead/behind, stashes, average health), without a row per repository
+        #[arg(long, conflicts_with_all = ["activity", "group"])]
+        summary_only: bool,
     },
     
+    /// Review and drop old stashes across all repositories
+    PruneStashes {
+        /// Only offer stashes at least this many days old (default: all stashes)
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<i64>,
+
+        /// Drop without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Abort in-progress merges, rebases, cherry-picks, reverts and bisects
+    Abort {
+        /// Abort without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// Initialize and update submodules that are missing or out of date
+    UpdateSubmodules,
+
+    /// Fast-forward every clean repository that is behind its upstream
+    PullBehind {
+        /// Pull without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Stop starting new pulls after the first one fails
+        #[arg(long)]
+        fail_fast: bool,
+    },
+
+    /// Add a remote to every repository that has none, to back it up
+    AddRemote {
+        /// Remote URL; {name} is replaced by the repository's directory name
+        url: String,
+
+        /// Name of the new remote
+        #[arg(long, default_value = "origin")]
+        name: String,
+
+        /// Push the current branch to the new remote and track it
+        #[arg(long)]
+        push: bool,
+
+        /// Add without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// List the linked worktrees of every repository
+    Worktrees {
+        /// Offer to remove worktrees whose directory no longer exists
+        #[arg(long)]
+        prune: bool,
+
+        /// Prune without asking for confirmation
+        #[arg(short, long, requires = "prune")]
+        yes: bool,
+    },
+
+    /// Report what changed since the last digest run, then update its snapshot
+    Digest,
+
+    /// Time repeated scans and report per-phase durations
+    Bench {
+        /// Number of scans to run
+        #[arg(short = 'n', long, default_value_t = 5, value_parser = clap::value_parser!(u32).range(1..))]
+        runs: u32,
+    },
+
     /// Generate example configuration file
     Config {
-        #[arg(short, long, default_value = ".git-nexus.toml")]
+        #[arg(short, long, default_value = ".git-nexus.toml", value_parser = expanded_path)]
         output: PathBuf,
     },
+
+    /// Check whether a newer release of git-nexus is available
+    CheckUpdate {
+        /// Ignore the result cached from today's earlier check
+        #[arg(long)]
+        refresh: bool,
+    },
+
+    /// Print a shell completion script to stdout
+    Completions {
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum GroupBy {
+    Label,
+    Branch,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputFormat {
+    Json,
+    Toml,
+    Yaml,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
@@ -78,219 +376,1408 @@ enum ExportFormat {
     Csv,
 }
 
-#[derive(Debug, Clone, ValueEnum)]
-enum StatusFilter {
-    Clean,
-    Dirty,
-    Ahead,
-    Behind,
-}
+#[derive(Debug, Clone, ValueEnum)]
+enum StatusFilter {
+    Clean,
+    Dirty,
+    Ahead,
+    Behind,
+    /// Repositories without any remote, whose work exists only locally
+    NoRemote,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+enum SortBy {
+    Path,
+    Status,
+    Branch,
+    /// Most attention needed first, weighted like --suggest priorities
+    Issues,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RepoStatus {
+    path: PathBuf,
+    is_clean: bool,
+    ahead: usize,
+    behind: usize,
+    branch: Option<String>,
+    /// The branch the current one tracks, e.g. `origin/main`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    upstream: Option<String>,
+    /// The current branch matches `important_branches`.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    important: bool,
+    /// The current branch matches `protected_branches`: work belongs on a
+    /// feature branch, not here.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    protected: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    labels: Vec<String>,
+    /// Cloned with `--depth`; ahead/behind counts may be incomplete.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    is_shallow: bool,
+    /// `HEAD` is on a branch with no commits while other local branches
+    /// exist: an orphan branch being started, or a checked-out branch that
+    /// was deleted.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    unborn_branch: bool,
+    /// At least one remote is configured; without one, nothing is backed up.
+    has_remote: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    repo_state: Option<operation::Operation>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    submodules: Option<submodules::SubmoduleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integrity: Option<integrity::Integrity>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stash_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stashes: Option<Vec<stash::StashEntry>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modified_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    untracked_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    changed_files: Option<Vec<ChangedFile>>,
+    /// Other paths leading to the same repository, with `--show-aliases`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aliases: Option<Vec<PathBuf>>,
+    /// How the superproject's pointer to this repository changed, with
+    /// `--changed-since`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pointer_move: Option<submodules::PointerMove>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_commit: Option<CommitInfo>,
+    /// With `-vvv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    remotes: Option<Vec<RemoteInfo>>,
+    /// The nearest tag reachable from `HEAD`, with `-vvv`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    latest_tag: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hooks: Option<hooks::GitHooks>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    github: Option<github::GitHubInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    health: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    suggestions: Option<Vec<suggest::Suggestion>>,
+    /// The `.git` directory is missing pieces, typically an interrupted clone.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    incomplete: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl RepoStatus {
+    /// A repository that has a `.git` directory but could not be analyzed.
+    fn failed(path: PathBuf, reason: String) -> Self {
+        Self {
+            path,
+            is_clean: false,
+            ahead: 0,
+            behind: 0,
+            branch: None,
+            upstream: None,
+            important: false,
+            protected: false,
+            labels: Vec::new(),
+            is_shallow: false,
+            unborn_branch: false,
+            has_remote: false,
+            repo_state: None,
+            submodules: None,
+            integrity: None,
+            stash_count: None,
+            stashes: None,
+            modified_count: None,
+            untracked_count: None,
+            changed_files: None,
+            aliases: None,
+            pointer_move: None,
+            last_commit: None,
+            remotes: None,
+            latest_tag: None,
+            hooks: None,
+            github: None,
+            health: None,
+            suggestions: None,
+            incomplete: false,
+            error: Some(reason),
+        }
+    }
+
+    /// The path to show to people: relative to `root` when given and the
+    /// repository lives under it (`.` for the root itself), as-is otherwise.
+    pub fn display_path(&self, root: Option<&Path>) -> String {
+        match root.and_then(|root| self.path.strip_prefix(root).ok()) {
+            Some(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+            Some(relative) => relative.display().to_string(),
+            None => self.path.display().to_string(),
+        }
+    }
+
+    /// Work that isn't safely upstream yet: local changes, unpushed commits
+    /// or stashes. Stashes are only counted when they were (verbosely)
+    /// collected.
+    pub fn has_pending_work(&self) -> bool {
+        self.error.is_none() && (!self.is_clean || self.ahead > 0 || self.stash_count.is_some_and(|s| s > 0))
+    }
+
+    pub fn is_detached(&self) -> bool {
+        self.branch.as_deref().is_some_and(|b| b.starts_with("detached@"))
+    }
+
+    pub fn status_label(&self) -> &'static str {
+        if self.error.is_some() {
+            "ERROR"
+        } else if self.is_clean {
+            "CLEAN"
+        } else {
+            "DIRTY"
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ScanError {
+    path: PathBuf,
+    reason: String,
+}
+
+/// `-v`: the last commit.
+pub const VERBOSE_COMMIT: u8 = 1;
+/// `-vv`: also stashes, file counts and hooks.
+pub const VERBOSE_DETAILS: u8 = 2;
+/// `-vvv`: also remotes, the latest tag and submodules.
+pub const VERBOSE_ALL: u8 = 3;
+
+/// Settings shared by every repository analyzed in one scan.
+#[derive(Clone, Copy)]
+pub struct ScanOptions<'a> {
+    pub max_depth: usize,
+    /// How much to collect per repository, one of the `VERBOSE_*` levels or 0.
+    pub verbosity: u8,
+    pub ignore: &'a IgnorePatterns,
+    pub show_hooks: bool,
+    pub sequential: bool,
+    pub important_branches: &'a [String],
+    pub protected_branches: &'a [String],
+    pub labels: &'a labels::Labels,
+    pub verify: bool,
+    pub date_format: &'a dates::DateFormat,
+    pub timezone: dates::Zone,
+    pub full_message: bool,
+    pub ignore_remotes: &'a [String],
+    pub list_files: bool,
+    pub ignore_untracked: bool,
+    pub explain_status: bool,
+    pub dedupe: bool,
+    pub show_aliases: bool,
+    pub refresh_ttl: Option<Duration>,
+    /// Also discover checkouts whose `.git` is a file, like submodules.
+    pub include_gitfiles: bool,
+    pub bare_repos: &'a [config::BareRepo],
+    /// Directories visited before `WalkHint` speaks up; 0 never.
+    pub walk_hint_after: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct ScanReport {
+    pub repos: Vec<RepoStatus>,
+    pub errors: Vec<ScanError>,
+    /// Repositories opted out with a `.git-nexus-skip` file.
+    pub skipped: Vec<PathBuf>,
+    /// Set when Ctrl-C cut the scan short: how many repositories had been
+    /// found by then (the walk may have stopped early too).
+    pub interrupted: Option<usize>,
+}
+
+#[derive(Serialize)]
+struct Report<'a, T> {
+    repos: &'a [T],
+    summary: &'a summary::IssueSummary,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    errors: Option<&'a [ScanError]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    skipped: Option<&'a [PathBuf]>,
+}
+
+/// One entry of `--list-files`, in `git status -s` notation.
+#[derive(Debug, Serialize, Clone)]
+pub struct ChangedFile {
+    /// Index and worktree status letters, e.g. `M `, ` M`, `A `, `??`, `UU`.
+    status: String,
+    path: String,
+    /// The libgit2 status flags behind `status`, with `--explain-status`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    flags: Vec<&'static str>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CommitInfo {
+    message: String,
+    author: String,
+    email: String,
+    timestamp: String,
+    /// Commit time as a Unix timestamp, for tools that format dates themselves.
+    seconds: i64,
+    hash: String,
+    /// The complete message, only kept with `--full-message`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemoteInfo {
+    name: String,
+    url: String,
+}
+
+/// Path arguments get `~` and `$VAR` expanded themselves, since shells leave
+/// them alone in quotes and after `--flag=`.
+fn expanded_path(arg: &str) -> Result<PathBuf, std::convert::Infallible> {
+    Ok(config::dirs::expand(Path::new(arg)))
+}
+
+fn main() -> Result<()> {
+    let mut cli = Cli::parse();
+    // Every machine format behaves like --json: no banners, no progress.
+    cli.json |= cli.output.is_some();
+    cli.show_hooks |= cli.verbose >= VERBOSE_DETAILS;
+    cli.plain_paths |= cli.null;
+    cli.list_files |= cli.short;
+    cli.suggest |= cli.audit;
+    if cli.collapse_branches {
+        if let Some(GroupBy::Label) = cli.group_by {
+            anyhow::bail!("--collapse-branches groups by branch and can't be combined with --group-by label");
+        }
+        cli.group_by = Some(GroupBy::Branch);
+    }
+    logging::init(cli.log_level, cli.log_file.as_deref())?;
+    let config = Config::load().unwrap_or_default();
+    glyphs::set_ascii(cli.ascii);
+    offline::set(cli.offline);
+    audit::set_thorough(cli.audit);
+    config.display.icons.validate()?;
+    glyphs::set_icons(config.display.icons.clone());
+
+    let mut ignore = IgnorePatterns::new(&config.ignore_dirs);
+    if let Some(ref ignore_file) = cli.ignore_file {
+        ignore.merge(IgnorePatterns::load_from_file(ignore_file)?);
+    }
+
+    let labels = labels::Labels::new(&config.labels);
+    let date_format = match cli.date.take() {
+        Some(format) => format,
+        None => dates::DateFormat::parse(&config.display.date_format).map_err(|e| anyhow::anyhow!("date_format: {}", e))?,
+    };
+    let timezone = match cli.timezone {
+        Some(zone) => zone,
+        None => dates::Zone::parse(&config.display.timezone).map_err(|e| anyhow::anyhow!("timezone: {}", e))?,
+    };
+    let scan_options = ScanOptions {
+        max_depth: cli.depth.unwrap_or(config.scan_depth),
+        // The last commit, which --author matches on, is only read verbosely.
+        // Reports always include the commit and file-count columns, and
+        // default_verbose collects what a single -v did before levels existed.
+        verbosity: cli
+            .verbose
+            .max(if cli.author.is_some() { VERBOSE_COMMIT } else { 0 })
+            .max(if cli.export.is_some() || config.display.default_verbose { VERBOSE_DETAILS } else { 0 }),
+        ignore: &ignore,
+        show_hooks: cli.show_hooks,
+        sequential: cli.sequential,
+        important_branches: &config.important_branches,
+        protected_branches: &config.protected_branches,
+        labels: &labels,
+        verify: cli.verify,
+        date_format: &date_format,
+        timezone,
+        full_message: cli.full_message,
+        ignore_remotes: &config.ignore_remotes,
+        list_files: cli.list_files,
+        ignore_untracked: cli.ignore_untracked,
+        explain_status: cli.explain_status,
+        dedupe: cli.dedupe,
+        show_aliases: cli.show_aliases,
+        refresh_ttl: cli.refresh_remotes_ttl.map(|minutes| Duration::from_secs(minutes * 60)),
+        include_gitfiles: cli.changed_since.is_some(),
+        bare_repos: &config.bare_repos,
+        walk_hint_after: config.walk_hint_after,
+    };
+
+    let display_root = cli.relative.then_some(cli.path.as_path());
+
+    // Handle subcommands
+    match cli.command {
+        Some(Commands::Tui) => {
+            let repos = scan_repositories(&cli.path, &scan_options).repos;
+            return tui::run_tui(repos);
+        }
+        Some(Commands::Watch { dashboard, on_change, poll, poll_interval }) => {
+            let backend = watch::Backend { poll, poll_interval: Duration::from_secs(poll_interval) };
+            return watch::watch_mode(&cli.path, &scan_options, dashboard, on_change.as_deref(), backend);
+        }
+        Some(Commands::Export { format, output, activity, group, theme, summary_only }) => {
+            let export_options = ScanOptions { verbosity: scan_options.verbosity.max(VERBOSE_DETAILS), ..scan_options };
+            let repos = scan_repositories(&cli.path, &export_options).repos;
+            let html_options = export::HtmlOptions {
+                activity,
+                group,
+                scan_root: cli.path.clone(),
+                theme,
+                max_path_len: cli.max_path_len,
+                summary_only,
+            };
+            write_export(&format, &repos, display_root, &output, &html_options)?;
+            println!("{} Exported to {}", glyphs::DONE, output.display());
+            return Ok(());
+        }
+        Some(Commands::PruneStashes { older_than, yes }) => {
+            let mut repos = scan_repositories(&cli.path, &scan_options).repos;
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            return stash::prune_stashes(&repos, older_than, yes);
+        }
+        Some(Commands::Abort { yes }) => {
+            let mut repos = scan_repositories(&cli.path, &scan_options).repos;
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            return operation::abort_operations(&repos, yes);
+        }
+        Some(Commands::UpdateSubmodules) => {
+            offline::ensure_online("update-submodules")?;
+            let mut repos = scan_repositories(&cli.path, &scan_options).repos;
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            return submodules::update_submodules(&repos);
+        }
+        Some(Commands::PullBehind { yes, fail_fast }) => {
+            offline::ensure_online("pull-behind")?;
+            let mut repos = scan_repositories(&cli.path, &scan_options).repos;
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            return pull::pull_behind(&repos, display_root, yes, fail_fast);
+        }
+        Some(Commands::AddRemote { url, name, push, yes }) => {
+            if push {
+                offline::ensure_online("add-remote --push")?;
+            }
+            let mut repos = scan_repositories(&cli.path, &scan_options).repos;
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            return remote::add_remotes(&repos, display_root, &url, &name, push, yes);
+        }
+        Some(Commands::Worktrees { prune, yes }) => {
+            let mut repos = scan_repositories(&cli.path, &scan_options).repos;
+            repos.sort_by(|a, b| a.path.cmp(&b.path));
+            return worktrees::show_worktrees(&repos, display_root, cli.json, prune, yes);
+        }
+        Some(Commands::Digest) => {
+            let repos = scan_repositories(&cli.path, &scan_options).repos;
+            return digest::run_digest(&cli.path, &repos, cli.json);
+        }
+        Some(Commands::Bench { runs }) => {
+            return bench::run_bench(&cli.path, &scan_options, runs, cli.json);
+        }
+        Some(Commands::Config { output }) => {
+            Config::create_example(&output)?;
+            println!("{} Created example config at {}", glyphs::DONE, output.display());
+            return Ok(());
+        }
+        Some(Commands::CheckUpdate { refresh }) => {
+            return update::check_update(refresh);
+        }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "git-nexus", &mut std::io::stdout());
+            return Ok(());
+        }
+        None => {}
+    }
+
+    let health_range = health::HealthRange { min: cli.min_health, max: cli.max_health };
+    let baseline = cli.baseline.as_deref().map(health::Baseline::load).transpose()?;
+    let only = only_patterns(&cli.path, &cli.only);
+    let hidden = |repo: &RepoStatus| cli.quiet_clean && !summary::needs_attention(repo);
+    let show = |repo: &RepoStatus| {
+        if cli.short {
+            display_repo_short(repo, display_root, cli.max_path_len);
+        } else {
+            display_repo_status(repo, display_root, cli.verbose, cli.show_hooks, cli.max_path_len, !cli.collapse_branches);
+        }
+    };
+    interrupt::install();
+    let pointer_moves = match cli.changed_since {
+        Some(ref rev) => Some(submodules::moved_since(&cli.path, rev)?),
+        None => None,
+    };
+
+    // Normal scan mode
+    if !cli.json && !cli.only_issues_exit && !cli.plain_paths {
+        println!("{}", format!("{} Scanning workspace for git repositories...", glyphs::SEARCH).bright_cyan().bold());
+        println!();
+    }
+
+    if cli.stream {
+        let blocker = if cli.json {
+            Some("JSON output")
+        } else if cli.sort.is_some() {
+            Some("--sort")
+        } else if cli.show_github {
+            Some("--show-github")
+        } else if cli.group_by.is_some() {
+            Some("--group-by")
+        } else if cli.export.is_some() {
+            Some("--export")
+        } else if cli.baseline.is_some() {
+            Some("--baseline")
+        } else {
+            None
+        };
+
+        match blocker {
+            Some(blocker) => eprintln!(
+                "{}",
+                format!("note: streaming disabled, {} needs the full result set", blocker).bright_black()
+            ),
+            None => {
+                let mut analyzed = 0;
+                let mut shown = 0;
+                let mut hidden_clean = 0;
+                let report = stream_repositories(&cli.path, &scan_options, |mut repo| {
+                    analyzed += 1;
+                    if matches_filter(&repo, cli.filter.as_ref())
+                        && matches_labels(&repo, &cli.label)
+                        && matches_only(&repo, &cli.path, &only)
+                        && matches_path(&repo, cli.path_regex.as_ref())
+                        && matches_author(&repo, cli.author.as_deref())
+                        && matches_health(&mut repo, &health_range)
+                        && matches_changed_since(&mut repo, pointer_moves.as_ref())
+                    {
+                        shown += 1;
+                        if hidden(&repo) {
+                            hidden_clean += 1;
+                            return;
+                        }
+                        if cli.suggest {
+                            repo.suggestions = Some(suggest::suggest(&repo));
+                        }
+                        show(&repo);
+                    }
+                });
+
+                if shown == 0 {
+                    println!("{}", "No git repositories found.".yellow());
+                } else {
+                    print_hidden_clean(hidden_clean);
+                    println!("\n{} {} repositories found", glyphs::FOUND.get().green().bold(), shown);
+                }
+                if cli.show_errors || cli.verbose > 0 {
+                    display_skipped(&report.skipped);
+                }
+                if cli.show_errors {
+                    display_scan_errors(&report.errors);
+                }
+                if let Some(found) = report.interrupted {
+                    print_interrupted(analyzed, found, false);
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
+                return check_strict(cli.strict, &report.errors);
+            }
+        }
+    }
+
+    let ScanReport { mut repos, errors, skipped, interrupted } = scan_repositories(&cli.path, &scan_options);
+    let analyzed = repos.len();
+    // The whole workspace is compared, whatever the filters below show.
+    let comparison = baseline.as_ref().map(|baseline| baseline.compare(&repos, display_root));
+
+    // Apply filter
+    repos.retain_mut(|r| {
+        matches_filter(r, cli.filter.as_ref())
+            && matches_labels(r, &cli.label)
+            && matches_only(r, &cli.path, &only)
+            && matches_path(r, cli.path_regex.as_ref())
+            && matches_author(r, cli.author.as_deref())
+            && matches_health(r, &health_range)
+            && matches_changed_since(r, pointer_moves.as_ref())
+    });
+
+    if cli.suggest {
+        for repo in &mut repos {
+            repo.suggestions = Some(suggest::suggest(repo));
+        }
+    }
+
+    // Sort repositories
+    match cli.sort.unwrap_or(SortBy::Path) {
+        SortBy::Path => repos.sort_by(|a, b| a.path.cmp(&b.path)),
+        SortBy::Status => repos.sort_by_key(|a| a.is_clean),
+        SortBy::Branch => repos.sort_by(|a, b| a.branch.cmp(&b.branch)),
+        SortBy::Issues => repos.sort_by(|a, b| {
+            suggest::issue_score(b).cmp(&suggest::issue_score(a)).then_with(|| a.path.cmp(&b.path))
+        }),
+    }
+
+    if cli.plain_paths {
+        print_paths(&repos, display_root, if cli.null { b'\0' } else { b'\n' })?;
+        if interrupted.is_some() {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        return check_strict(cli.strict, &errors);
+    }
+
+    if cli.only_issues_exit {
+        let found = report_issues(&repos, display_root);
+        if interrupted.is_some() {
+            std::process::exit(INTERRUPTED_EXIT_CODE);
+        }
+        if found {
+            std::process::exit(1);
+        }
+        return check_strict(cli.strict, &errors);
+    }
+
+    let mut github_summary = None;
+    if cli.show_github && offline::enabled() {
+        offline::note_skipped("--show-github");
+    } else if cli.show_github {
+        let github_config = config.github.clone().unwrap_or_default();
+        let token = github_config.resolve_token();
+        let options = github::FetchOptions {
+            token: token.as_deref(),
+            cache_ttl: github_config.cache_ttl,
+            refresh: cli.refresh_github,
+            show_progress: !cli.json && config.display.show_progress,
+        };
+        github_summary = Some(github::fetch_all(&mut repos, &options));
+    }
+
+    if cli.json {
+        let format = cli.output.unwrap_or(OutputFormat::Json);
+        let issues = summary::IssueSummary::from_repos(&repos);
+        if cli.flat {
+            print_report(&flat::flatten(&repos), &issues, &errors, &skipped, cli.show_errors, format, cli.compact)?;
+        } else {
+            print_report(&repos, &issues, &errors, &skipped, cli.show_errors, format, cli.compact)?;
+        }
+    } else {
+        if repos.is_empty() {
+            println!("{}", "No git repositories found.".yellow());
+        } else {
+            println!("{} {} repositories found\n", glyphs::FOUND.get().green().bold(), repos.len());
+            if config.display.attention_banner {
+                println!("{}\n", summary::IssueSummary::from_repos(&repos).banner(&config.display));
+            }
+            match cli.group_by {
+                Some(GroupBy::Label) => {
+                    for (label, group) in group_by_label(&repos) {
+                        println!("{}", format!("{} ({})", label, group.len()).bold().underline());
+                        for repo in group.into_iter().filter(|r| !hidden(r)) {
+                            show(repo);
+                        }
+                        println!();
+                    }
+                }
+                Some(GroupBy::Branch) => {
+                    for (branch, group) in group_by_branch(&repos) {
+                        let marker = if group.iter().any(|r| r.important) { format!(" {}", glyphs::IMPORTANT) } else { String::new() };
+                        println!("{}", format!("{}{} ({})", branch, marker, group.len()).bright_blue().bold().underline());
+                        for repo in group.into_iter().filter(|r| !hidden(r)) {
+                            show(repo);
+                        }
+                        println!();
+                    }
+                }
+                None => {
+                    for repo in repos.iter().filter(|r| !hidden(r)) {
+                        show(repo);
+                    }
+                }
+            }
+            print_hidden_clean(repos.iter().filter(|r| hidden(r)).count());
+        }
+
+        if let (Some(comparison), Some(path)) = (&comparison, &cli.baseline) {
+            display_health_comparison(comparison, path, cli.verbose > 0);
+        }
+        if cli.show_errors || cli.verbose > 0 {
+            display_skipped(&skipped);
+        }
+        if cli.show_errors {
+            display_scan_errors(&errors);
+        }
+    }
+
+    // On stderr in every mode, after the listing so it isn't scrolled away.
+    if let Some(ref summary) = github_summary {
+        summary.report();
+    }
+
+    if let (Some(format), Some(path)) = (&cli.export, &cli.export_to) {
+        let html_options = export::HtmlOptions { max_path_len: cli.max_path_len, ..Default::default() };
+        write_export(format, &repos, display_root, path, &html_options)?;
+        // Keep machine-readable stdout clean.
+        if cli.json {
+            eprintln!("{} Exported to {}", glyphs::DONE, path.display());
+        } else {
+            println!("{} Exported to {}", glyphs::DONE, path.display());
+        }
+    }
+
+    if let Some(found) = interrupted {
+        // Post-scan hooks expect a complete scan, so they don't run.
+        print_interrupted(analyzed, found, cli.json);
+        std::process::exit(INTERRUPTED_EXIT_CODE);
+    }
+
+    post_scan::run_post_scan_hooks(&repos, &config, cli.trust_local_config)?;
+
+    check_strict(cli.strict, &errors)
+}
+
+fn display_health_comparison(comparison: &health::Comparison, baseline: &Path, verbose: bool) {
+    let (Some(before), Some(after)) = (comparison.before, comparison.after) else {
+        println!("{}", format!("No repositories to compare with {}", baseline.display()).yellow());
+        return;
+    };
+
+    let delta = format!("{:+}", after as i16 - before as i16);
+    let delta = match after.cmp(&before) {
+        std::cmp::Ordering::Greater => delta.green(),
+        std::cmp::Ordering::Less => delta.red(),
+        std::cmp::Ordering::Equal => delta.normal(),
+    };
+    println!();
+    println!("Workspace health {}% {} {}% ({}) since {}", before, glyphs::ARROW.get(), after, delta.bold(), baseline.display());
+
+    if verbose {
+        for (path, before, after) in comparison.changes.iter().filter(|(_, before, after)| before != after) {
+            let delta = *after as i16 - *before as i16;
+            println!("   {} {} {} {} {} ({:+})", glyphs::TREE.get().bright_black(), path, before, glyphs::ARROW.get(), after, delta);
+        }
+    }
+}
+
+/// The conventional exit code for a process stopped by SIGINT.
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Tell that the results are partial; on stderr with machine output, which
+/// is valid but incomplete.
+fn print_interrupted(analyzed: usize, found: usize, json: bool) {
+    let note = format!("{} Scan interrupted, showing {} of ~{} repositories", glyphs::WARNING, analyzed, found);
+    if json {
+        eprintln!("{}", note);
+    } else {
+        println!("\n{}", note.yellow().bold());
+    }
+}
+
+/// `--plain-paths` / `-0`: nothing but the paths, written as raw bytes so
+/// names that aren't valid UTF-8 survive the trip through `xargs`.
+fn print_paths(repos: &[RepoStatus], root: Option<&Path>, separator: u8) -> Result<()> {
+    let mut out = std::io::stdout().lock();
+    for repo in repos {
+        let path = match root.and_then(|root| repo.path.strip_prefix(root).ok()) {
+            Some(relative) if relative.as_os_str().is_empty() => Path::new("."),
+            Some(relative) => relative,
+            None => repo.path.as_path(),
+        };
+        out.write_all(path.as_os_str().as_encoded_bytes())?;
+        out.write_all(&[separator])?;
+    }
+    out.flush()?;
+    Ok(())
+}
+
+/// `--only-issues-exit`: a terse line per repository that needs attention,
+/// and a count. Returns whether there was any.
+fn report_issues(repos: &[RepoStatus], root: Option<&Path>) -> bool {
+    let problems: Vec<&RepoStatus> = repos.iter().filter(|r| summary::needs_attention(r)).collect();
+    for repo in &problems {
+        println!("{}: {}", repo.display_path(root), summary::attention_reasons(repo).join(", "));
+    }
+    if !problems.is_empty() {
+        println!("{} of {} repositories need attention", problems.len(), repos.len());
+    }
+    !problems.is_empty()
+}
+
+/// The `--quiet-clean` stand-in for the repositories it left out.
+fn print_hidden_clean(count: usize) {
+    if count > 0 {
+        println!("{}", format!("+{} clean repos (hidden)", count).bright_black());
+    }
+}
+
+fn write_export(
+    format: &ExportFormat,
+    repos: &[RepoStatus],
+    root: Option<&Path>,
+    path: &PathBuf,
+    html_options: &export::HtmlOptions,
+) -> Result<()> {
+    match format {
+        ExportFormat::Html => export::export_html(repos, root, path, html_options),
+        ExportFormat::Csv if html_options.summary_only => export::export_summary_csv(repos, path),
+        ExportFormat::Csv => export::export_csv(repos, root, path),
+    }
+}
+
+/// Print the repositories as a JSON or YAML list, or wrapped in a
+/// `{repos, summary, errors, skipped}` envelope when scan errors were asked for. TOML
+/// has no top-level arrays, so it always uses the envelope.
+fn print_report<T: Serialize>(
+    repos: &[T],
+    summary: &summary::IssueSummary,
+    errors: &[ScanError],
+    skipped: &[PathBuf],
+    show_errors: bool,
+    format: OutputFormat,
+    compact: bool,
+) -> Result<()> {
+    let report = Report {
+        repos,
+        summary,
+        errors: show_errors.then_some(errors),
+        skipped: show_errors.then_some(skipped),
+    };
+
+    match format {
+        OutputFormat::Json if show_errors => println!("{}", to_json(&report, compact)?),
+        OutputFormat::Json if !repos.is_empty() => println!("{}", to_json(&repos, compact)?),
+        OutputFormat::Json => {}
+        OutputFormat::Yaml if show_errors => print!("{}", serde_yaml::to_string(&report)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(&repos)?),
+        OutputFormat::Toml => print!("{}", toml::to_string_pretty(&report)?),
+    }
+    Ok(())
+}
+
+fn to_json<T: Serialize + ?Sized>(value: &T, compact: bool) -> serde_json::Result<String> {
+    if compact {
+        serde_json::to_string(value)
+    } else {
+        serde_json::to_string_pretty(value)
+    }
+}
+
+fn matches_filter(repo: &RepoStatus, filter: Option<&StatusFilter>) -> bool {
+    match filter {
+        None => true,
+        Some(StatusFilter::Clean) => repo.is_clean,
+        Some(StatusFilter::Dirty) => !repo.is_clean && repo.error.is_none(),
+        Some(StatusFilter::Ahead) => repo.ahead > 0,
+        Some(StatusFilter::Behind) => repo.behind > 0,
+        Some(StatusFilter::NoRemote) => !repo.has_remote && repo.error.is_none(),
+    }
+}
+
+fn matches_labels(repo: &RepoStatus, labels: &[String]) -> bool {
+    labels.is_empty() || repo.labels.iter().any(|l| labels.contains(l))
+}
+
+/// Repositories per label, in label order. A repository with several labels
+/// appears under each of them; unlabeled ones come last.
+fn group_by_label(repos: &[RepoStatus]) -> Vec<(String, Vec<&RepoStatus>)> {
+    let mut groups: std::collections::BTreeMap<&str, Vec<&RepoStatus>> = Default::default();
+    let mut unlabeled = Vec::new();
+    for repo in repos {
+        if repo.labels.is_empty() {
+            unlabeled.push(repo);
+        }
+        for label in &repo.labels {
+            groups.entry(label).or_default().push(repo);
+        }
+    }
+
+    let mut grouped: Vec<_> = groups.into_iter().map(|(label, group)| (label.to_string(), group)).collect();
+    if !unlabeled.is_empty() {
+        grouped.push(("(no label)".to_string(), unlabeled));
+    }
+    grouped
+}
+
+/// Repositories by current branch, the most common branch first. Ones
+/// without a branch (failed scans) come last.
+fn group_by_branch(repos: &[RepoStatus]) -> Vec<(String, Vec<&RepoStatus>)> {
+    let mut groups: std::collections::BTreeMap<&str, Vec<&RepoStatus>> = Default::default();
+    let mut unknown = Vec::new();
+    for repo in repos {
+        match repo.branch.as_deref() {
+            Some(branch) => groups.entry(branch).or_default().push(repo),
+            None => unknown.push(repo),
+        }
+    }
+
+    let mut grouped: Vec<_> = groups.into_iter().map(|(branch, group)| (branch.to_string(), group)).collect();
+    // Stable, so equally common branches stay in name order.
+    grouped.sort_by_key(|(_, group)| std::cmp::Reverse(group.len()));
+    if !unknown.is_empty() {
+        grouped.push(("(no branch)".to_string(), unknown));
+    }
+    grouped
+}
+
+/// Build the `--only` list. Paths under the scan root may be given as-is
+/// (`~/work/api`) and are made relative to it, like the ignore patterns.
+fn only_patterns(root: &Path, only: &[String]) -> IgnorePatterns {
+    let mut patterns = IgnorePatterns::default();
+    for pattern in only {
+        let relative = Path::new(pattern)
+            .strip_prefix(root)
+            .map(|p| p.to_string_lossy().into_owned())
+            .unwrap_or_else(|_| pattern.trim_start_matches("./").to_string());
+        patterns.add(&relative);
+    }
+    patterns
+}
+
+fn matches_only(repo: &RepoStatus, root: &Path, only: &IgnorePatterns) -> bool {
+    if only.is_empty() {
+        return true;
+    }
+    let relative = repo.path.strip_prefix(root).unwrap_or(&repo.path);
+    only.matches_within(relative)
+}
+
+/// Keep only the repositories `--changed-since` found a moved pointer for,
+/// and record the move on them.
+fn matches_changed_since(repo: &mut RepoStatus, moves: Option<&HashMap<PathBuf, submodules::PointerMove>>) -> bool {
+    let Some(moves) = moves else {
+        return true;
+    };
+    let key = repo.path.canonicalize().unwrap_or_else(|_| repo.path.clone());
+    repo.pointer_move = moves.get(&key).cloned();
+    repo.pointer_move.is_some()
+}
+
+/// Matched against the absolute, resolved path rather than the one walked
+/// (`./x/y` for the default root), so patterns anchored at `/` work.
+fn matches_path(repo: &RepoStatus, regex: Option<&Regex>) -> bool {
+    regex.is_none_or(|re| {
+        let path = repo.path.canonicalize().unwrap_or_else(|_| repo.path.clone());
+        re.is_match(&path.to_string_lossy())
+    })
+}
+
+/// Case-insensitive substring match against the last commit's author name
+/// and email. Repositories without commits never match.
+fn matches_author(repo: &RepoStatus, author: Option<&str>) -> bool {
+    let Some(author) = author else {
+        return true;
+    };
+    let needle = author.to_lowercase();
+    repo.last_commit.as_ref().is_some_and(|c| {
+        c.author.to_lowercase().contains(&needle) || c.email.to_lowercase().contains(&needle)
+    })
+}
+
+/// Score the repository when a health bound is set, keeping the score for
+/// display and JSON, and check it against the bounds.
+fn matches_health(repo: &mut RepoStatus, range: &health::HealthRange) -> bool {
+    if !range.is_active() {
+        return true;
+    }
+    let score = health::calculate_health_score(repo);
+    repo.health = Some(score);
+    range.matches(score)
+}
+
+fn check_strict(strict: bool, errors: &[ScanError]) -> Result<()> {
+    if strict && !errors.is_empty() {
+        anyhow::bail!("{} paths could not be scanned (run with --show-errors for details)", errors.len());
+    }
+    Ok(())
+}
+
+/// Number of analyzed repositories that may wait in the channel while the
+/// printer catches up.
+const STREAM_BUFFER: usize = 64;
+
+pub fn scan_repositories(root: &PathBuf, options: &ScanOptions) -> ScanReport {
+    let started = Instant::now();
+    let (git_dirs, errors) = discover_repositories(root, options);
+    let (git_dirs, mut aliases) = dedupe_repositories(git_dirs, options);
+    let (skipped, git_dirs) = split_skipped(git_dirs);
+    if let Some(ttl) = options.refresh_ttl {
+        refresh::refresh_stale(&git_dirs, ttl);
+    }
+    let mut report = ScanReport { repos: Vec::new(), errors, skipped, interrupted: None };
+
+    for (mut status, error) in analyze_all(root, &git_dirs, options) {
+        status.aliases = aliases.remove(&status.path);
+        report.repos.push(status);
+        report.errors.extend(error);
+    }
+    if interrupt::requested() {
+        report.interrupted = Some(git_dirs.len());
+    }
+    log::info!(
+        "scanned {} repositories under {} in {:?} ({} errors)",
+        report.repos.len(),
+        root.display(),
+        started.elapsed(),
+        report.errors.len()
+    );
+    report
+}
+
+/// Analyze the discovered repositories, in parallel unless `--sequential`.
+/// After Ctrl-C, repositories not yet started are left out.
+fn analyze_all(root: &Path, git_dirs: &[PathBuf], options: &ScanOptions) -> Vec<(RepoStatus, Option<ScanError>)> {
+    let analyze = |path| {
+        if interrupt::requested() {
+            return None;
+        }
+        analyze_or_report(root, path, options)
+    };
+    if options.sequential {
+        git_dirs.iter().filter_map(analyze).collect()
+    } else {
+        git_dirs.par_iter().filter_map(analyze).collect()
+    }
+}
+
+/// Like `scan_repositories`, but hands each repository to `on_repo` as soon as
+/// it has been analyzed instead of collecting them all first. Results pass
+/// through a bounded channel, so memory use stays flat for huge workspaces.
+/// The returned report has no `repos`.
+pub fn stream_repositories(root: &PathBuf, options: &ScanOptions, mut on_repo: impl FnMut(RepoStatus)) -> ScanReport {
+    let (git_dirs, mut errors) = discover_repositories(root, options);
+    let (git_dirs, mut aliases) = dedupe_repositories(git_dirs, options);
+    let (skipped, git_dirs) = split_skipped(git_dirs);
+    if let Some(ttl) = options.refresh_ttl {
+        refresh::refresh_stale(&git_dirs, ttl);
+    }
+    let found = git_dirs.len();
+    let (tx, rx) = mpsc::sync_channel(STREAM_BUFFER);
+
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            if options.sequential {
+                for path in &git_dirs {
+                    if interrupt::requested() {
+                        break;
+                    }
+                    if let Some(result) = analyze_or_report(root, path, options)
+                        && tx.send(result).is_err()
+                    {
+                        break;
+                    }
+                }
+            } else {
+                git_dirs.par_iter().for_each_with(tx, |tx, path| {
+                    if !interrupt::requested()
+                        && let Some(result) = analyze_or_report(root, path, options)
+                    {
+                        let _ = tx.send(result);
+                    }
+                });
+            }
+        });
+
+        for (mut status, error) in rx {
+            errors.extend(error);
+            status.aliases = aliases.remove(&status.path);
+            on_repo(status);
+        }
+    });
+
+    let interrupted = interrupt::requested().then_some(found);
+    ScanReport { repos: Vec::new(), errors, skipped, interrupted }
+}
+
+/// Walk `root` and collect every directory containing a `.git`, along with
+/// any directories that could not be read.
+fn discover_repositories(root: &PathBuf, options: &ScanOptions) -> (Vec<PathBuf>, Vec<ScanError>) {
+    // Depth 0 skips the walk entirely: the root is the repository or nothing.
+    if options.max_depth == 0 {
+        let mut git_dirs = if root.join(".git").exists() {
+            vec![root.clone()]
+        } else {
+            Vec::new()
+        };
+        git_dirs.extend(configured_bare_repos(root, options));
+        return (git_dirs, Vec::new());
+    }
+
+    let started = Instant::now();
+    log::debug!("walking {} to depth {}", root.display(), options.max_depth);
+    let mut ignore_files = IgnoreFiles::default();
+
+    let mut errors = Vec::new();
+    let mut git_dirs: Vec<PathBuf> = Vec::new();
+    let mut hint = WalkHint::new(options.walk_hint_after);
+
+    let walker = WalkDir::new(root)
+        .max_depth(options.max_depth)
+        .follow_links(options.dedupe)
+        .into_iter()
+        .filter_entry(|e| {
+            if e.file_type().is_dir() && e.file_name() != ".git" {
+                let relative = e.path().strip_prefix(root).unwrap_or(e.path());
+                if options.ignore.is_ignored(relative) || ignore_files.is_ignored(root, e.path()) {
+                    log::debug!("skipping ignored directory {}", e.path().display());
+                    return false;
+                }
+                true
+            } else {
+                true
+            }
+        });
+
+    // An unreadable directory yields an error entry and the walk carries on
+    // with its siblings, so record it rather than dropping it.
+    for entry in walker {
+        if interrupt::requested() {
+            log::info!("walk of {} interrupted", root.display());
+            break;
+        }
+        match entry {
+            Ok(e) => {
+                if e.file_type().is_dir() {
+                    hint.visit(root, e.path(), options.max_depth);
+                }
+                // Submodule checkouts have a `.git` file pointing into the
+                // superproject; they only count for --changed-since.
+                if (e.file_type().is_dir() || (options.include_gitfiles && e.file_type().is_file()))
+                    && e.file_name() == ".git"
+                    && let Some(parent) = e.path().parent()
+                {
+                    git_dirs.push(parent.to_path_buf());
+                }
+            }
+            // Following links can lead back up the tree; walkdir stops there.
+            Err(e) if e.loop_ancestor().is_some() => {
+                log::debug!("not following symlink loop at {}", e.path().unwrap_or(root).display());
+            }
+            Err(e) => {
+                let error = ScanError {
+                    path: e.path().map(|p| p.to_path_buf()).unwrap_or_else(|| root.clone()),
+                    reason: e
+                        .io_error()
+                        .map(|io| io.to_string())
+                        .unwrap_or_else(|| e.to_string()),
+                };
+                log::warn!("cannot read {}: {}", error.path.display(), error.reason);
+                errors.push(error);
+            }
+        }
+    }
+
+    git_dirs.extend(configured_bare_repos(root, options));
+    log::debug!(
+        "found {} repositories under {} in {:?}",
+        git_dirs.len(),
+        root.display(),
+        started.elapsed()
+    );
 
-#[derive(Debug, Clone, ValueEnum)]
-enum SortBy {
-    Path,
-    Status,
-    Branch,
+    (git_dirs, errors)
 }
 
-#[derive(Debug, Serialize, Clone)]
-pub struct RepoStatus {
-    path: PathBuf,
-    is_clean: bool,
-    ahead: usize,
-    behind: usize,
-    branch: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    stash_count: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    modified_count: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    untracked_count: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    last_commit: Option<CommitInfo>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    hooks: Option<hooks::GitHooks>,
-}
+/// Shown once per process: watch mode walks again on every change.
+static WALK_HINT_SHOWN: AtomicBool = AtomicBool::new(false);
 
-#[derive(Debug, Serialize, Clone)]
-pub struct CommitInfo {
-    message: String,
-    author: String,
-    timestamp: String,
-    hash: String,
+/// Directories listed in the walk hint, biggest first.
+const WALK_HINT_TOP: usize = 3;
+
+/// Counts the directories a walk visits, per top-level directory under the
+/// root, and suggests `--depth` or ignores once the walk gets huge (usually
+/// a home directory full of `node_modules`).
+struct WalkHint {
+    threshold: usize,
+    visited: usize,
+    by_top_dir: HashMap<PathBuf, usize>,
 }
 
-fn main() -> Result<()> {
-    let cli = Cli::parse();
-    let config = Config::load().unwrap_or_default();
+impl WalkHint {
+    fn new(threshold: usize) -> Self {
+        Self { threshold, visited: 0, by_top_dir: HashMap::new() }
+    }
 
-    // Handle subcommands
-    match cli.command {
-        Some(Commands::Tui) => {
-            let repos = scan_repositories(
-                &cli.path,
-                cli.depth.unwrap_or(config.scan_depth),
-                cli.verbose || config.display.default_verbose,
-                &config.ignore_dirs,
-                cli.show_hooks,
-            );
-            return tui::run_tui(repos);
+    fn visit(&mut self, root: &Path, dir: &Path, max_depth: usize) {
+        if self.threshold == 0 {
+            return;
         }
-        Some(Commands::Watch) => {
-            return watch::watch_mode(&cli.path, &config, cli.verbose);
-        }
-        Some(Commands::Export { format, output }) => {
-            let repos = scan_repositories(
-                &cli.path,
-                cli.depth.unwrap_or(config.scan_depth),
-                true,
-                &config.ignore_dirs,
-                cli.show_hooks,
-            );
-            
-            match format {
-                ExportFormat::Html => export::export_html(&repos, &output)?,
-                ExportFormat::Csv => export::export_csv(&repos, &output)?,
-            }
-            
-            println!("✅ Exported to {}", output.display());
-            return Ok(());
+        self.visited += 1;
+        if let Some(top) = dir.strip_prefix(root).ok().and_then(|p| p.components().next()) {
+            *self.by_top_dir.entry(root.join(top)).or_default() += 1;
         }
-        Some(Commands::Config { output }) => {
-            Config::create_example(&output)?;
-            println!("✅ Created example config at {}", output.display());
-            return Ok(());
+        if self.visited == self.threshold && !WALK_HINT_SHOWN.swap(true, Ordering::Relaxed) {
+            self.show(max_depth);
         }
-        None => {}
     }
 
-    // Normal scan mode
-    if !cli.json {
-        println!("{}", "🔍 Scanning workspace for git repositories...".bright_cyan().bold());
-        println!();
+    fn show(&self, max_depth: usize) {
+        let mut biggest: Vec<(&PathBuf, &usize)> = self.by_top_dir.iter().collect();
+        biggest.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        let biggest: Vec<String> = biggest
+            .into_iter()
+            .take(WALK_HINT_TOP)
+            .map(|(path, count)| format!("{} ({})", path.display(), count))
+            .collect();
+        eprintln!(
+            "{} {} directories visited and still walking (--depth {}). Most are under {}.",
+            glyphs::SUGGEST,
+            self.visited,
+            max_depth,
+            biggest.join(", ")
+        );
+        eprintln!(
+            "   {}",
+            "Lower --depth, or skip them with ignore_dirs or a .git-nexus-ignore file. \
+             Set walk_hint_after = 0 in the config to silence this."
+                .bright_black()
+        );
     }
+}
 
-    let mut repos = scan_repositories(
-        &cli.path,
-        cli.depth.unwrap_or(config.scan_depth),
-        cli.verbose || config.display.default_verbose,
-        &config.ignore_dirs,
-        cli.show_hooks,
-    );
+/// The configured `bare_repos` whose work tree is inside `root`, by git dir.
+/// The walk cannot find them, since their work tree has no `.git`.
+fn configured_bare_repos(root: &Path, options: &ScanOptions) -> Vec<PathBuf> {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    options
+        .bare_repos
+        .iter()
+        .filter(|bare| bare.work_tree.canonicalize().is_ok_and(|work_tree| work_tree.starts_with(&root)))
+        .map(|bare| bare.git_dir.clone())
+        .collect()
+}
 
-    // Apply filter
-    if let Some(ref filter) = cli.filter {
-        repos.retain(|r| match filter {
-            StatusFilter::Clean => r.is_clean,
-            StatusFilter::Dirty => !r.is_clean,
-            StatusFilter::Ahead => r.ahead > 0,
-            StatusFilter::Behind => r.behind > 0,
-        });
-    }
+/// Per-repository opt-out: a repository with this file at its root is not
+/// analyzed.
+const SKIP_MARKER: &str = ".git-nexus-skip";
 
-    // Sort repositories
-    match cli.sort {
-        SortBy::Path => repos.sort_by(|a, b| a.path.cmp(&b.path)),
-        SortBy::Status => repos.sort_by(|a, b| a.is_clean.cmp(&b.is_clean)),
-        SortBy::Branch => repos.sort_by(|a, b| a.branch.cmp(&b.branch)),
+/// Separate the repositories carrying a `SKIP_MARKER` (first) from the ones
+/// to analyze.
+fn split_skipped(git_dirs: Vec<PathBuf>) -> (Vec<PathBuf>, Vec<PathBuf>) {
+    git_dirs.into_iter().partition(|path| {
+        let skip = path.join(SKIP_MARKER).exists();
+        if skip {
+            log::debug!("skipping {}: {} present", path.display(), SKIP_MARKER);
+        }
+        skip
+    })
+}
+
+/// With `--dedupe`, keep one path per physical repository (by its
+/// canonicalized `.git`), preferring the shortest. With `--show-aliases`, the
+/// other paths are returned keyed by the kept one.
+fn dedupe_repositories(git_dirs: Vec<PathBuf>, options: &ScanOptions) -> (Vec<PathBuf>, HashMap<PathBuf, Vec<PathBuf>>) {
+    if !options.dedupe {
+        return (git_dirs, HashMap::new());
     }
 
-    if repos.is_empty() {
-        if !cli.json {
-            println!("{}", "No git repositories found.".yellow());
+    let mut by_git_dir: HashMap<PathBuf, Vec<PathBuf>> = HashMap::new();
+    let mut order = Vec::new();
+    for path in git_dirs {
+        let key = path.join(".git").canonicalize().unwrap_or_else(|_| path.clone());
+        let paths = by_git_dir.entry(key.clone()).or_default();
+        if paths.is_empty() {
+            order.push(key);
         }
-        return Ok(());
+        paths.push(path);
     }
 
-    if cli.json {
-        let json = serde_json::to_string_pretty(&repos)?;
-        println!("{}", json);
-    } else {
-        println!("{} {} repositories found\n", "✓".green().bold(), repos.len());
-        for repo in repos {
-            display_repo_status(&repo, cli.verbose, cli.show_hooks);
+    let mut kept = Vec::with_capacity(order.len());
+    let mut aliases = HashMap::new();
+    for key in order {
+        let mut paths = by_git_dir.remove(&key).unwrap_or_default();
+        paths.sort_by_key(|p| (p.components().count(), p.as_os_str().len()));
+        let primary = paths.remove(0);
+        if !paths.is_empty() {
+            log::debug!("{} is also reachable as {:?}", primary.display(), paths);
+        }
+        if options.show_aliases {
+            aliases.insert(primary.clone(), paths);
         }
+        kept.push(primary);
     }
+    (kept, aliases)
+}
 
-    Ok(())
+/// Analyze one repository. Failures still produce a `RepoStatus` (marked as
+/// an error) plus the matching `ScanError` for the error report; `None`
+/// means the repository is excluded by `ignore_remotes`.
+fn analyze_or_report(root: &Path, path: &PathBuf, options: &ScanOptions) -> Option<(RepoStatus, Option<ScanError>)> {
+    let started = Instant::now();
+    let (mut status, error) = match analyze_repository(path, options) {
+        Ok(None) => return None,
+        Ok(Some(status)) => {
+            log::debug!("analyzed {} in {:?}", path.display(), started.elapsed());
+            (status, None)
+        }
+        Err(e) => {
+            let incomplete = incomplete_git_dir(path);
+            let reason = match incomplete {
+                Some(missing) => format!("incomplete .git directory ({}), probably an interrupted clone", missing),
+                None => e.message().to_string(),
+            };
+            log::warn!("failed to analyze {}: {}", path.display(), reason);
+            let error = ScanError {
+                path: path.clone(),
+                reason: reason.clone(),
+            };
+            let mut status = RepoStatus::failed(path.clone(), reason);
+            status.incomplete = incomplete.is_some();
+            (status, Some(error))
+        }
+    };
+    status.labels = options.labels.for_path(root, path);
+    Some((status, error))
 }
 
-pub fn scan_repositories(
-    root: &PathBuf,
-    max_depth: usize,
-    verbose: bool,
-    ignore_dirs: &[String],
-    show_hooks: bool,
-) -> Vec<RepoStatus> {
-    let git_dirs: Vec<PathBuf> = WalkDir::new(root)
-        .max_depth(max_depth)
-        .into_iter()
-        .filter_entry(|e| {
-            if e.file_type().is_dir() {
-                let name = e.file_name().to_string_lossy();
-                !ignore_dirs.contains(&name.to_string()) || name == ".git"
-            } else {
-                true
-            }
-        })
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_dir() && e.file_name() == ".git")
-        .filter_map(|e| e.path().parent().map(|p| p.to_path_buf()))
-        .collect();
-
-    git_dirs
-        .par_iter()
-        .filter_map(|path| analyze_repository(path, verbose, show_hooks))
-        .collect()
+/// What is missing from `path/.git` when it looks like a clone that never
+/// finished: no `HEAD`, or no objects at all. A `.git` file (worktree or
+/// submodule link) is never reported.
+fn incomplete_git_dir(path: &Path) -> Option<&'static str> {
+    let git_dir = path.join(".git");
+    if !git_dir.is_dir() {
+        return None;
+    }
+    if !git_dir.join("HEAD").is_file() {
+        return Some("missing HEAD");
+    }
+    match std::fs::read_dir(git_dir.join("objects")) {
+        Err(_) => Some("missing objects directory"),
+        Ok(mut entries) => entries.next().is_none().then_some("empty objects directory"),
+    }
 }
 
-fn analyze_repository(path: &std::path::Path, verbose: bool, show_hooks: bool) -> Option<RepoStatus> {
-    let repo = Repository::open(path).ok()?;
+fn analyze_repository(path: &PathBuf, options: &ScanOptions) -> Result<Option<RepoStatus>, git2::Error> {
+    let bare = options.bare_repos.iter().find(|bare| bare.git_dir == *path);
+    let repo = match bare {
+        Some(bare) => {
+            let repo = Repository::open_bare(&bare.git_dir)?;
+            repo.set_workdir(&bare.work_tree, false)?;
+            repo
+        }
+        None => Repository::open(path)?,
+    };
+
+    if let Some(url) = ignored_remote(&repo, options.ignore_remotes) {
+        log::debug!("skipping {}: origin {} matches ignore_remotes", path.display(), url);
+        return Ok(None);
+    }
 
+    // A work tree like `$HOME` is mostly files the repository doesn't track,
+    // so only tracked files count there.
     let mut opts = StatusOptions::new();
-    opts.include_untracked(true);
+    opts.include_untracked(bare.is_none());
     opts.include_ignored(false);
 
-    let statuses = repo.statuses(Some(&mut opts)).ok()?;
-    let is_clean = statuses.is_empty();
+    let statuses = repo.statuses(Some(&mut opts))?;
+    // Untracked files are still listed and counted, they just don't make
+    // the repository dirty.
+    let is_clean = if options.ignore_untracked {
+        statuses.iter().all(|entry| entry.status() == git2::Status::WT_NEW)
+    } else {
+        statuses.is_empty()
+    };
 
     let branch = get_current_branch(&repo);
+    let matches_any = |patterns: &[String]| {
+        branch.as_deref().is_some_and(|b| patterns.iter().any(|pattern| ignore::glob_matches(pattern, b)))
+    };
+    let important = matches_any(options.important_branches);
+    let protected = matches_any(options.protected_branches);
+    let repo_state = operation::Operation::detect(&repo);
+    let submodules = submodules::SubmoduleStatus::detect(&repo);
     let (ahead, behind) = get_branch_divergence(&repo).unwrap_or((0, 0));
 
-    let (stash_count, modified_count, untracked_count, last_commit) = if verbose {
+    let last_commit = if options.verbosity >= VERBOSE_COMMIT {
+        get_last_commit(&repo, options)
+    } else {
+        None
+    };
+
+    let (stash_count, stashes, modified_count, untracked_count) = if options.verbosity >= VERBOSE_DETAILS {
         let stash = count_stashes(&repo);
+        // Listing needs a mutable handle, so it opens the repository again;
+        // skip that when there is nothing to list.
+        let stashes = if stash > 0 { stash::list_stashes(path).ok() } else { None };
         let (modified, untracked) = count_file_changes(&statuses);
-        let commit = get_last_commit(&repo);
-        (Some(stash), Some(modified), Some(untracked), commit)
+        (Some(stash), stashes, Some(modified), Some(untracked))
     } else {
         (None, None, None, None)
     };
 
-    let hooks = if show_hooks {
+    let (remotes, latest_tag) = if options.verbosity >= VERBOSE_ALL {
+        (Some(list_remotes(&repo)), get_latest_tag(&repo))
+    } else {
+        (None, None)
+    };
+
+    let hooks = if options.show_hooks {
         hooks::GitHooks::detect(path)
     } else {
         None
     };
 
-    Some(RepoStatus {
+    let integrity = options.verify.then(|| integrity::verify(path));
+    let changed_files =
+        (options.list_files || options.explain_status).then(|| list_changed_files(&statuses, options.explain_status));
+
+    Ok(Some(RepoStatus {
         path: path.to_path_buf(),
         is_clean,
         ahead,
         behind,
         branch,
+        upstream: get_upstream(&repo),
+        important,
+        protected,
+        labels: Vec::new(),
+        is_shallow: repo.is_shallow(),
+        unborn_branch: is_unborn_among_branches(&repo),
+        has_remote: repo.remotes().is_ok_and(|remotes| !remotes.is_empty()),
+        repo_state,
+        submodules,
+        integrity,
         stash_count,
+        stashes,
         modified_count,
         untracked_count,
+        changed_files,
+        aliases: None,
+        pointer_move: None,
         last_commit,
+        remotes,
+        latest_tag,
         hooks,
-    })
+        github: None,
+        health: None,
+        suggestions: None,
+        incomplete: false,
+        error: None,
+    }))
+}
+
+/// The `origin` URL, if it matches one of the `ignore_remotes` patterns.
+/// Plain text matches anywhere in the URL; patterns with glob characters
+/// must match the whole URL.
+fn ignored_remote(repo: &Repository, patterns: &[String]) -> Option<String> {
+    if patterns.is_empty() {
+        return None;
+    }
+    let remote = repo.find_remote("origin").ok()?;
+    let url = remote.url()?;
+    patterns
+        .iter()
+        .any(|pattern| {
+            if pattern.contains(['*', '?']) {
+                ignore::glob_matches(pattern, url)
+            } else {
+                url.contains(pattern.as_str())
+            }
+        })
+        .then(|| url.to_string())
 }
 
 fn get_current_branch(repo: &Repository) -> Option<String> {
     match repo.head() {
         Ok(head) => {
             if head.is_branch() {
-                head.shorthand().map(|s| s.to_string())
+                Some(String::from_utf8_lossy(head.shorthand_bytes()).into_owned())
             } else if let Some(target) = head.target() {
                 Some(format!("detached@{}", &target.to_string()[..7]))
             } else {
@@ -298,11 +1785,11 @@ fn get_current_branch(repo: &Repository) -> Option<String> {
             }
         }
         Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {
-            if let Ok(reference) = repo.find_reference("HEAD") {
-                if let Some(target) = reference.symbolic_target() {
-                    let branch = target.strip_prefix("refs/heads/").unwrap_or(target);
-                    return Some(format!("{} (no commits)", branch));
-                }
+            if let Ok(reference) = repo.find_reference("HEAD")
+                && let Some(target) = reference.symbolic_target()
+            {
+                let branch = target.strip_prefix("refs/heads/").unwrap_or(target);
+                return Some(format!("{} (no commits)", branch));
             }
             Some("(no commits)".to_string())
         }
@@ -310,6 +1797,29 @@ fn get_current_branch(repo: &Repository) -> Option<String> {
     }
 }
 
+/// `HEAD` points to a branch without commits while other local branches
+/// exist. libgit2 reports this the same way as a fresh repository; only
+/// local branches count, so a fresh clone-in-progress with just fetched
+/// remote refs is not flagged.
+fn is_unborn_among_branches(repo: &Repository) -> bool {
+    match repo.head() {
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => repo
+            .branches(Some(git2::BranchType::Local))
+            .is_ok_and(|mut branches| branches.any(|b| b.is_ok())),
+        _ => false,
+    }
+}
+
+/// The shorthand of the current branch's upstream, e.g. `origin/main`.
+fn get_upstream(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    let upstream = git2::Branch::wrap(head).upstream().ok()?;
+    upstream.name().ok().flatten().map(String::from)
+}
+
 fn get_branch_divergence(repo: &Repository) -> Result<(usize, usize), git2::Error> {
     let head = repo.head()?;
 
@@ -320,8 +1830,9 @@ fn get_branch_divergence(repo: &Repository) -> Result<(usize, usize), git2::Erro
     let local_commit = head.peel_to_commit()?;
     let local_oid = local_commit.id();
 
-    let branch = repo.find_branch(head.shorthand().unwrap(), git2::BranchType::Local)?;
-    let upstream = branch.upstream();
+    // Wrap the reference instead of looking the branch up by its shorthand,
+    // which is not always valid UTF-8.
+    let upstream = git2::Branch::wrap(head).upstream();
 
     if let Ok(upstream_branch) = upstream {
         let upstream_oid = upstream_branch.get().peel_to_commit()?.id();
@@ -340,6 +1851,45 @@ fn count_stashes(repo: &Repository) -> usize {
     }
 }
 
+fn list_changed_files(statuses: &git2::Statuses, with_flags: bool) -> Vec<ChangedFile> {
+    use git2::Status;
+
+    statuses
+        .iter()
+        .map(|entry| {
+            let status = entry.status();
+            let code = if status.is_conflicted() {
+                "UU".to_string()
+            } else if status.is_wt_new() {
+                "??".to_string()
+            } else {
+                let letter = |flags: &[(Status, char)]| {
+                    flags.iter().find(|(flag, _)| status.contains(*flag)).map_or(' ', |&(_, c)| c)
+                };
+                let index = letter(&[
+                    (Status::INDEX_NEW, 'A'),
+                    (Status::INDEX_MODIFIED, 'M'),
+                    (Status::INDEX_DELETED, 'D'),
+                    (Status::INDEX_RENAMED, 'R'),
+                    (Status::INDEX_TYPECHANGE, 'T'),
+                ]);
+                let worktree = letter(&[
+                    (Status::WT_MODIFIED, 'M'),
+                    (Status::WT_DELETED, 'D'),
+                    (Status::WT_RENAMED, 'R'),
+                    (Status::WT_TYPECHANGE, 'T'),
+                ]);
+                format!("{}{}", index, worktree)
+            };
+            ChangedFile {
+                status: code,
+                path: entry.path().unwrap_or("?").to_string(),
+                flags: if with_flags { status.iter_names().map(|(name, _)| name).collect() } else { Vec::new() },
+            }
+        })
+        .collect()
+}
+
 fn count_file_changes(statuses: &git2::Statuses) -> (usize, usize) {
     let mut modified = 0;
     let mut untracked = 0;
@@ -366,91 +1916,558 @@ fn count_file_changes(statuses: &git2::Statuses) -> (usize, usize) {
     (modified, untracked)
 }
 
-fn get_last_commit(repo: &Repository) -> Option<CommitInfo> {
+fn list_remotes(repo: &Repository) -> Vec<RemoteInfo> {
+    let Ok(names) = repo.remotes() else {
+        return Vec::new();
+    };
+    names
+        .iter()
+        .flatten()
+        .filter_map(|name| {
+            let remote = repo.find_remote(name).ok()?;
+            Some(RemoteInfo { name: name.to_string(), url: remote.url()?.to_string() })
+        })
+        .collect()
+}
+
+/// Like `git describe --tags --abbrev=0`.
+fn get_latest_tag(repo: &Repository) -> Option<String> {
+    let describe = repo.describe(git2::DescribeOptions::new().describe_tags()).ok()?;
+    describe.format(Some(git2::DescribeFormatOptions::new().abbreviated_size(0))).ok()
+}
+
+fn get_last_commit(repo: &Repository, options: &ScanOptions) -> Option<CommitInfo> {
     let head = repo.head().ok()?;
     let commit = head.peel_to_commit().ok()?;
 
-    let message = commit.message().unwrap_or("").lines().next().unwrap_or("").to_string();
+    let message = commit.message().unwrap_or("").lines().next().unwrap_or("").trim_end().to_string();
     let author = commit.author().name().unwrap_or("Unknown").to_string();
-    let timestamp = commit.time();
-    let datetime: DateTime<Local> = DateTime::from_timestamp(timestamp.seconds(), 0)?.into();
+    let email = commit.author().email().unwrap_or("").to_string();
+    let seconds = commit.time().seconds();
+    let timestamp = options.date_format.format(seconds, options.timezone)?;
+    let full_message = options.full_message.then(|| clean_message(commit.message().unwrap_or("")));
     let hash = commit.id().to_string()[..7].to_string();
 
     Some(CommitInfo {
         message,
         author,
-        timestamp: datetime.format("%Y-%m-%d %H:%M:%S").to_string(),
+        email,
+        timestamp,
+        seconds,
         hash,
+        full_message,
     })
 }
 
-pub fn display_repo_status(status: &RepoStatus, verbose: bool, show_hooks: bool) {
-    let path_display = status.path.display().to_string();
+/// Normalize a commit message for display: CRLF line endings become LF,
+/// trailing whitespace is stripped from every line and trailing blank lines
+/// are dropped.
+fn clean_message(message: &str) -> String {
+    message
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end()
+        .to_string()
+}
+
+fn display_skipped(skipped: &[PathBuf]) {
+    if skipped.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{} {} repositories skipped ({})", glyphs::SEARCH, skipped.len(), SKIP_MARKER);
+    for path in skipped {
+        println!("   {} {}", glyphs::TREE.get().bright_black(), path.display().to_string().bright_black());
+    }
+}
+
+fn display_scan_errors(errors: &[ScanError]) {
+    if errors.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("{} {} paths could not be scanned", glyphs::WARNING.get().yellow().bold(), errors.len());
+    for error in errors {
+        println!("   {} {}: {}", glyphs::TREE.get().bright_black(), error.path.display().to_string().bright_white(), error.reason.red());
+    }
+}
+
+/// fsck complaints printed per repository; the full list is in the JSON.
+const MAX_FSCK_LINES: usize = 5;
+
+/// `--list-files` entries printed per repository; the full list is in the JSON.
+const MAX_LISTED_FILES: usize = 20;
+
+/// Shorten `path` to at most `max` characters by dropping whole directories
+/// from the middle (`/home/…/project/repo`), keeping the first component and
+/// as many trailing ones as fit. When even the last component is too long,
+/// characters are cut from its middle instead.
+fn shorten_path(path: &str, max: Option<usize>) -> String {
+    let Some(max) = max else {
+        return path.to_string();
+    };
+    let len = |s: &str| s.chars().count();
+    if len(path) <= max {
+        return path.to_string();
+    }
+
+    let components: Vec<&str> = path.split('/').collect();
+    if let [first, .., last] = components.as_slice() {
+        let mut tail = last.to_string();
+        let head = format!("{}/{}/", first, glyphs::ELLIPSIS);
+        if len(&head) + len(&tail) <= max {
+            for component in components[1..components.len() - 1].iter().rev() {
+                let longer = format!("{}/{}", component, tail);
+                if len(&head) + len(&longer) > max {
+                    break;
+                }
+                tail = longer;
+            }
+            return format!("{}{}", head, tail);
+        }
+    }
+
+    // No room for whole components: keep both ends of the characters.
+    let chars: Vec<char> = path.chars().collect();
+    let keep = max.saturating_sub(len(glyphs::ELLIPSIS.get()));
+    let front = keep / 2;
+    let back = keep - front;
+    let mut short: String = chars[..front].iter().collect();
+    short.push_str(glyphs::ELLIPSIS.get());
+    short.extend(&chars[chars.len() - back..]);
+    short
+}
+
+/// One repository in the layout of `git status -sb`: the path, a
+/// `## branch...upstream [ahead N, behind M]` header and the changed files.
+fn display_repo_short(status: &RepoStatus, root: Option<&Path>, max_path_len: Option<usize>) {
+    let path_display = shorten_path(&status.display_path(root), max_path_len);
+    if let Some(ref error) = status.error {
+        println!("{} {} {}", path_display.bold(), "##".bright_black(), format!("error: {}", error).red());
+        return;
+    }
+
+    let mut header = match status.branch.as_deref() {
+        Some(branch) if branch.starts_with("detached@") => "HEAD (no branch)".red().to_string(),
+        Some(branch) => match branch.strip_suffix(" (no commits)") {
+            Some(branch) => format!("No commits yet on {}", branch.green()),
+            None => branch.green().to_string(),
+        },
+        None => "HEAD (no branch)".red().to_string(),
+    };
+    if let Some(ref upstream) = status.upstream {
+        header.push_str(&format!("...{}", upstream.red()));
+        let divergence: Vec<String> = [("ahead", status.ahead), ("behind", status.behind)]
+            .into_iter()
+            .filter(|&(_, count)| count > 0)
+            .map(|(label, count)| format!("{} {}", label, count))
+            .collect();
+        if !divergence.is_empty() {
+            header.push_str(&format!(" [{}]", divergence.join(", ")));
+        }
+    }
+    println!("{} {} {}", path_display.bold(), "##".bright_black(), header);
+
+    if let Some(ref files) = status.changed_files {
+        for file in files.iter().take(MAX_LISTED_FILES) {
+            let code = match file.status.as_str() {
+                "??" => file.status.red(),
+                _ => file.status.green(),
+            };
+            println!("{} {}", code, file.path);
+        }
+        if files.len() > MAX_LISTED_FILES {
+            println!("{}", format!("{} and {} more", glyphs::ELLIPSIS, files.len() - MAX_LISTED_FILES).bright_black());
+        }
+    }
+}
+
+/// `show_branch` is false when the branch is already named in a group header
+/// (`--collapse-branches`).
+pub fn display_repo_status(
+    status: &RepoStatus,
+    root: Option<&Path>,
+    verbosity: u8,
+    show_hooks: bool,
+    max_path_len: Option<usize>,
+    show_branch: bool,
+) {
+    let path_display = shorten_path(&status.display_path(root), max_path_len);
+
+    let repo_icon = glyphs::icon(|i| &i.repo, glyphs::REPO.get());
+
+    if let Some(ref error) = status.error {
+        let label = if status.incomplete { "INCOMPLETE" } else { glyphs::icon(|i| &i.error, "ERROR") };
+        println!("{} {} [{}]", repo_icon, path_display.bright_white().bold(), format!("{} {}", glyphs::WARNING, label).yellow().bold());
+        println!("   {} {}", glyphs::TREE.get().bright_black(), error.yellow());
+        if status.incomplete && status.suggestions.is_none() {
+            println!("   {} {}", glyphs::TREE.get().bright_black(), "re-clone it, or run `git fsck` to see what can be recovered".bright_black());
+        }
+        display_suggestions(status);
+        return;
+    }
 
     let status_label = if status.is_clean {
-        "CLEAN".green().bold()
+        glyphs::icon(|i| &i.clean, "CLEAN").green().bold()
     } else {
-        "DIRTY".red().bold()
+        glyphs::icon(|i| &i.dirty, "DIRTY").red().bold()
     };
 
-    let branch_display = if let Some(ref branch) = status.branch {
-        format!(" ({})", branch).bright_blue().to_string()
+    let branch_display = if let Some(ref branch) = status.branch
+        && show_branch
+    {
+        if status.important {
+            format!(" ({} {})", branch, glyphs::IMPORTANT).bright_blue().bold().to_string()
+        } else {
+            format!(" ({})", branch).bright_blue().to_string()
+        }
     } else {
         String::new()
     };
 
-    print!("📁 {}{} [{}]", path_display.bright_white().bold(), branch_display, status_label);
+    print!("{} {}{} [{}]", repo_icon, path_display.bright_white().bold(), branch_display, status_label);
+
+    if let Some(operation) = status.repo_state {
+        print!(" {}", format!("{} {}", glyphs::PAUSED, operation.label()).magenta().bold());
+    }
+
+    if status.unborn_branch {
+        print!(" {}", "unborn branch".yellow());
+    }
+
+    for label in &status.labels {
+        print!(" {}", format!("#{}", label).cyan());
+    }
+
+    if let Some(health) = status.health {
+        let score = format!("health {}", health);
+        let score = match health {
+            80.. => score.green(),
+            50..80 => score.yellow(),
+            _ => score.red(),
+        };
+        print!(" ({})", score);
+    }
+
+    if status.is_shallow {
+        print!(" {}", glyphs::SHALLOW.get().bright_black());
+    }
+
+    if !status.has_remote {
+        print!(" {}", format!("{} local-only", glyphs::WARNING).yellow());
+    }
 
     if status.ahead > 0 {
-        print!(" {}{}", "↑".yellow(), status.ahead.to_string().yellow());
+        print!(" {}{}", glyphs::icon(|i| &i.ahead, glyphs::AHEAD.get()).yellow(), status.ahead.to_string().yellow());
     }
 
     if status.behind > 0 {
-        print!(" {}{}", "↓".red(), status.behind.to_string().red());
+        print!(" {}{}", glyphs::icon(|i| &i.behind, glyphs::BEHIND.get()).red(), status.behind.to_string().red());
     }
 
-    if verbose {
-        if let Some(stash) = status.stash_count {
-            if stash > 0 {
-                print!(" {}📦{}", " ".clear(), stash.to_string().bright_magenta());
-            }
+    if verbosity >= VERBOSE_DETAILS {
+        if let Some(stash) = status.stash_count
+            && stash > 0
+        {
+            print!(" {}{}{}", " ".clear(), glyphs::icon(|i| &i.stash, glyphs::STASH.get()), stash.to_string().bright_magenta());
+        }
+
+        if let Some(modified) = status.modified_count
+            && modified > 0
+        {
+            print!(" {}~{}", " ".clear(), modified.to_string().bright_yellow());
+        }
+
+        if let Some(untracked) = status.untracked_count
+            && untracked > 0
+        {
+            print!(" {}+{}", " ".clear(), untracked.to_string().bright_cyan());
         }
+    }
+
+    if show_hooks
+        && let Some(ref hooks) = status.hooks
+        && hooks.has_any()
+    {
+        print!(" {}{}{}", " ".clear(), glyphs::HOOKS, hooks.active_hooks().len().to_string().bright_magenta());
+    }
 
-        if let Some(modified) = status.modified_count {
-            if modified > 0 {
-                print!(" {}~{}", " ".clear(), modified.to_string().bright_yellow());
+    println!();
+
+    if verbosity >= VERBOSE_COMMIT
+        && let Some(ref commit) = status.last_commit
+    {
+        let sep = glyphs::SEPARATOR.get().bright_black();
+        println!("   {} {} {} {} {} {}", glyphs::TREE.get().bright_black(), commit.hash.bright_black(), sep, commit.author.bright_black(), sep, commit.message.bright_black());
+        // The subject is already on the line above; show the body under it.
+        if let Some(ref message) = commit.full_message {
+            for line in message.lines().skip(1).skip_while(|l| l.is_empty()) {
+                println!("      {}", line.bright_black());
             }
         }
+    }
+
+    if verbosity >= VERBOSE_ALL {
+        display_repo_details(status);
+    }
+
+    if let Some(ref aliases) = status.aliases {
+        for alias in aliases {
+            println!("   {} {}", glyphs::TREE.get().bright_black(), format!("also at {}", alias.display()).bright_black());
+        }
+    }
 
-        if let Some(untracked) = status.untracked_count {
-            if untracked > 0 {
-                print!(" {}+{}", " ".clear(), untracked.to_string().bright_cyan());
+    if let Some(ref pointer_move) = status.pointer_move {
+        println!("   {} {}", glyphs::TREE.get().bright_black(), pointer_move.describe().bright_black());
+    }
+
+    if let Some(ref files) = status.changed_files {
+        // With --explain-status every entry is shown: the point is to see
+        // all of them.
+        let explain = files.iter().any(|f| !f.flags.is_empty());
+        let limit = if explain { files.len() } else { MAX_LISTED_FILES };
+        for file in files.iter().take(limit) {
+            let code = match file.status.as_str() {
+                "??" => file.status.bright_cyan(),
+                "UU" => file.status.red().bold(),
+                _ => file.status.yellow(),
+            };
+            if explain {
+                println!("      {} {} {}", code, file.path, format!("[{}]", file.flags.join(" | ")).bright_black());
+            } else {
+                println!("      {} {}", code, file.path);
             }
         }
+        if files.len() > limit {
+            println!("      {}", format!("{} and {} more", glyphs::ELLIPSIS, files.len() - limit).bright_black());
+        }
+    }
+
+    if verbosity >= VERBOSE_DETAILS
+        && let Some(ref stashes) = status.stashes
+    {
+        for stash in stashes {
+            println!(
+                "   {} {} stash@{{{}}} {} {}",
+                glyphs::TREE.get().bright_black(),
+                glyphs::STASH,
+                stash.index,
+                stash.message.bright_black(),
+                format!("({})", stash.age()).bright_black()
+            );
+        }
     }
 
-    if show_hooks {
-        if let Some(ref hooks) = status.hooks {
-            if hooks.has_any() {
-                print!(" {}🪝{}", " ".clear(), hooks.active_hooks().len().to_string().bright_magenta());
+    if let Some(ref integrity) = status.integrity {
+        if integrity.ok {
+            println!("   {} fsck: {}", glyphs::TREE.get().bright_black(), "ok".green());
+        } else {
+            let summary = match integrity.missing_objects {
+                0 => format!("{} problems", integrity.problems.len()),
+                missing => format!("{} problems, {} missing objects", integrity.problems.len(), missing),
+            };
+            println!("   {} fsck: {}", glyphs::TREE.get().bright_black(), summary.red().bold());
+            for problem in integrity.problems.iter().take(MAX_FSCK_LINES) {
+                println!("      {}", problem.bright_black());
+            }
+            if integrity.problems.len() > MAX_FSCK_LINES {
+                println!("      {}", format!("... and {} more", integrity.problems.len() - MAX_FSCK_LINES).bright_black());
             }
         }
     }
 
-    println!();
+    if show_hooks
+        && let Some(ref hooks) = status.hooks
+        && hooks.has_any()
+    {
+        let hooks_list = hooks.active_hooks().join(", ");
+        println!("   {} hooks: {}", glyphs::TREE.get().bright_black(), hooks_list.bright_black());
+    }
 
-    if verbose {
-        if let Some(ref commit) = status.last_commit {
-            println!("   {} {} · {} · {}", "└─".bright_black(), commit.hash.bright_black(), commit.author.bright_black(), commit.message.bright_black());
+    match status.github {
+        Some(github::GitHubInfo::Fetched { open_issues, open_prs }) => println!(
+            "   {} GitHub: {} open issues {} {} open PRs",
+            glyphs::TREE.get().bright_black(),
+            open_issues.to_string().bright_green(),
+            glyphs::SEPARATOR,
+            open_prs.to_string().bright_green()
+        ),
+        Some(github::GitHubInfo::RateLimited { .. }) => println!(
+            "   {} GitHub: {}",
+            glyphs::TREE.get().bright_black(),
+            "rate limited, counts unavailable".yellow()
+        ),
+        Some(github::GitHubInfo::NotFound) => println!(
+            "   {} GitHub: {}",
+            glyphs::TREE.get().bright_black(),
+            "not found (private without a token, or deleted)".yellow()
+        ),
+        Some(github::GitHubInfo::Failed { ref reason }) => println!(
+            "   {} GitHub: {}",
+            glyphs::TREE.get().bright_black(),
+            format!("unavailable ({})", reason).yellow()
+        ),
+        None => {}
+    }
+
+    display_suggestions(status);
+}
+
+/// The `-vvv` lines: latest tag, remotes and submodules.
+fn display_repo_details(status: &RepoStatus) {
+    let tree = glyphs::TREE.get().bright_black();
+    if let Some(ref tag) = status.latest_tag {
+        println!("   {} tag {}", tree, tag.bright_black());
+    }
+    for remote in status.remotes.iter().flatten() {
+        println!("   {} remote {} {}", tree, remote.name, remote.url.bright_black());
+    }
+    if let Some(ref submodules) = status.submodules {
+        let mut problems = Vec::new();
+        if !submodules.uninitialized.is_empty() {
+            problems.push(format!("{} uninitialized", submodules.uninitialized.len()));
+        }
+        if !submodules.out_of_date.is_empty() {
+            problems.push(format!("{} out of date", submodules.out_of_date.len()));
         }
+        let detail = if problems.is_empty() { String::new() } else { format!(" ({})", problems.join(", ")) };
+        println!("   {} submodules {}{}", tree, submodules.total, detail.yellow());
     }
+}
 
-    if show_hooks {
-        if let Some(ref hooks) = status.hooks {
-            if hooks.has_any() {
-                let hooks_list = hooks.active_hooks().join(", ");
-                println!("   {} hooks: {}", "└─".bright_black(), hooks_list.bright_black());
-            }
+fn display_suggestions(status: &RepoStatus) {
+    for suggestion in status.suggestions.iter().flatten() {
+        let priority = format!("{:<8}", suggestion.priority.label());
+        let priority = match suggestion.priority {
+            suggest::Priority::Critical => priority.red().bold(),
+            suggest::Priority::High => priority.red(),
+            suggest::Priority::Medium => priority.yellow(),
+            suggest::Priority::Low => priority.bright_black(),
+        };
+        print!("   {} {} {} {}", glyphs::TREE.get().bright_black(), glyphs::SUGGEST, priority, suggestion.message);
+        match &suggestion.command {
+            Some(command) => println!(" {}", format!("({})", command).bright_black()),
+            None => println!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A repository with one empty commit on `main`.
+    fn repo_with_commit(dir: &Path) -> Repository {
+        let repo = Repository::init(dir).unwrap();
+        {
+            let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+            let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+            repo.commit(Some("refs/heads/main"), &signature, &signature, "initial", &tree, &[]).unwrap();
         }
+        repo.set_head("refs/heads/main").unwrap();
+        repo
+    }
+
+    /// Run `f` with the options of a plain scan.
+    fn with_scan_options<R>(f: impl FnOnce(&ScanOptions) -> R) -> R {
+        let ignore = IgnorePatterns::default();
+        let labels = labels::Labels::default();
+        let date_format = dates::DateFormat::Iso;
+        f(&ScanOptions {
+            max_depth: 3,
+            verbosity: 0,
+            ignore: &ignore,
+            show_hooks: false,
+            sequential: true,
+            important_branches: &[],
+            protected_branches: &[],
+            labels: &labels,
+            verify: false,
+            date_format: &date_format,
+            timezone: dates::Zone::Utc,
+            full_message: false,
+            ignore_remotes: &[],
+            list_files: false,
+            ignore_untracked: false,
+            explain_status: false,
+            dedupe: false,
+            show_aliases: false,
+            refresh_ttl: None,
+            include_gitfiles: false,
+            bare_repos: &[],
+            walk_hint_after: 0,
+        })
+    }
+
+    #[test]
+    fn dangling_symbolic_head_is_an_unborn_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = repo_with_commit(dir.path());
+        repo.reference_symbolic("HEAD", "refs/heads/gone", true, "test").unwrap();
+
+        assert!(is_unborn_among_branches(&repo));
+        assert_eq!(get_current_branch(&repo).as_deref(), Some("gone (no commits)"));
+        assert_eq!(get_branch_divergence(&repo).ok(), None);
+    }
+
+    #[test]
+    fn fresh_repository_is_not_an_unborn_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert!(!is_unborn_among_branches(&repo));
+    }
+
+    #[test]
+    fn fetched_remote_refs_alone_are_not_an_unborn_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let source = repo_with_commit(&dir.path().join("source"));
+        let target = source.head().unwrap().target().unwrap();
+
+        let repo = Repository::init(dir.path().join("clone")).unwrap();
+        repo.reference("refs/remotes/origin/main", target, false, "test").ok();
+        assert!(!is_unborn_among_branches(&repo));
+    }
+
+    /// A branch name that is not UTF-8 used to panic the whole scan.
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_branch_does_not_stop_the_scan() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        repo_with_commit(&dir.path().join("normal"));
+        let odd = repo_with_commit(&dir.path().join("odd"));
+        let commit = odd.head().unwrap().target().unwrap();
+        let heads = odd.path().join("refs/heads");
+        std::fs::write(heads.join(std::ffi::OsStr::from_bytes(b"f\xffx")), format!("{}\n", commit)).unwrap();
+        std::fs::write(odd.path().join("HEAD"), b"ref: refs/heads/f\xffx\n").unwrap();
+
+        let mut report = with_scan_options(|options| scan_repositories(&dir.path().to_path_buf(), options));
+        report.repos.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert!(report.errors.is_empty());
+        let branches: Vec<_> = report.repos.iter().map(|r| (r.error.is_none(), r.branch.as_deref())).collect();
+        assert_eq!(branches, [(true, Some("main")), (true, Some("f\u{FFFD}x"))]);
+    }
+
+    #[test]
+    fn ignore_untracked_counts_untracked_only_repositories_as_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        repo_with_commit(dir.path());
+        std::fs::write(dir.path().join("scratch.txt"), "notes").unwrap();
+        let path = dir.path().to_path_buf();
+
+        with_scan_options(|options| {
+            let status = analyze_repository(&path, options).unwrap().unwrap();
+            assert!(!status.is_clean);
+
+            let options = ScanOptions { ignore_untracked: true, ..*options };
+            let status = analyze_repository(&path, &options).unwrap().unwrap();
+            assert!(status.is_clean);
+        });
     }
 }
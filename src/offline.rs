@@ -0,0 +1,27 @@
+use anyhow::{bail, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static OFFLINE: AtomicBool = AtomicBool::new(false);
+
+/// Turn off everything that would touch the network (`--offline` /
+/// `GIT_NEXUS_OFFLINE`).
+pub fn set(offline: bool) {
+    OFFLINE.store(offline, Ordering::Relaxed);
+}
+
+pub fn enabled() -> bool {
+    OFFLINE.load(Ordering::Relaxed)
+}
+
+/// Fail a command that cannot work without the network.
+pub fn ensure_online(what: &str) -> Result<()> {
+    if enabled() {
+        bail!("{} needs the network, which --offline turns off", what);
+    }
+    Ok(())
+}
+
+/// Say on stderr that `what` was skipped, keeping stdout clean for JSON.
+pub fn note_skipped(what: &str) {
+    eprintln!("note: {} skipped in offline mode", what);
+}
@@ -0,0 +1,140 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use git2::{Repository, RepositoryState};
+use serde::Serialize;
+use std::path::Path;
+use std::process::Command;
+
+use crate::prompt::confirm;
+use crate::{glyphs, RepoStatus};
+
+/// A multi-step git operation left in progress (a paused rebase, a merge
+/// with conflicts, ...), detected from `repo.state()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Operation {
+    Merge,
+    Revert,
+    CherryPick,
+    Rebase,
+    Bisect,
+    ApplyMailbox,
+}
+
+impl Operation {
+    pub fn detect(repo: &Repository) -> Option<Self> {
+        match repo.state() {
+            RepositoryState::Clean => None,
+            RepositoryState::Merge => Some(Self::Merge),
+            RepositoryState::Revert | RepositoryState::RevertSequence => Some(Self::Revert),
+            RepositoryState::CherryPick | RepositoryState::CherryPickSequence => Some(Self::CherryPick),
+            RepositoryState::Rebase
+            | RepositoryState::RebaseInteractive
+            | RepositoryState::RebaseMerge
+            | RepositoryState::ApplyMailboxOrRebase => Some(Self::Rebase),
+            RepositoryState::Bisect => Some(Self::Bisect),
+            RepositoryState::ApplyMailbox => Some(Self::ApplyMailbox),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Merge => "MERGING",
+            Self::Revert => "REVERTING",
+            Self::CherryPick => "CHERRY-PICKING",
+            Self::Rebase => "REBASING",
+            Self::Bisect => "BISECTING",
+            Self::ApplyMailbox => "APPLYING PATCHES",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Merge => "Merge",
+            Self::Revert => "Revert",
+            Self::CherryPick => "Cherry-pick",
+            Self::Rebase => "Rebase",
+            Self::Bisect => "Bisect",
+            Self::ApplyMailbox => "git am",
+        }
+    }
+
+    /// The command that resumes the operation, if it can be resumed.
+    pub fn continue_command(&self) -> Option<&'static str> {
+        match self {
+            Self::Merge => Some("git merge --continue"),
+            Self::Revert => Some("git revert --continue"),
+            Self::CherryPick => Some("git cherry-pick --continue"),
+            Self::Rebase => Some("git rebase --continue"),
+            Self::Bisect => None,
+            Self::ApplyMailbox => Some("git am --continue"),
+        }
+    }
+
+    pub fn abort_command(&self) -> &'static str {
+        match self {
+            Self::Merge => "git merge --abort",
+            Self::Revert => "git revert --abort",
+            Self::CherryPick => "git cherry-pick --abort",
+            Self::Rebase => "git rebase --abort",
+            Self::Bisect => "git bisect reset",
+            Self::ApplyMailbox => "git am --abort",
+        }
+    }
+}
+
+/// Abort the operation in progress. This runs git's own `--abort` (or
+/// `bisect reset`), which restores the pre-operation state while keeping
+/// unrelated local changes, something `cleanup_state` plus a hard reset would
+/// throw away.
+pub fn abort(repo_path: &Path, operation: Operation) -> Result<()> {
+    let args: Vec<&str> = operation.abort_command().split_whitespace().skip(1).collect();
+    log::info!("running git {} in {}", args.join(" "), repo_path.display());
+    let output = Command::new("git")
+        .args(&args)
+        .current_dir(repo_path)
+        .output()
+        .context("failed to run git")?;
+
+    if !output.status.success() {
+        bail!("{}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+    Ok(())
+}
+
+/// Offer to abort every in-progress operation among the scanned
+/// repositories, confirming each one unless `yes` is set.
+pub fn abort_operations(repos: &[RepoStatus], yes: bool) -> Result<()> {
+    let mut aborted = 0;
+    let mut found = 0;
+
+    for repo in repos {
+        let Some(operation) = repo.repo_state else {
+            continue;
+        };
+        found += 1;
+
+        println!(
+            "{} {} {}",
+            glyphs::REPO,
+            repo.path.display().to_string().bold(),
+            format!("{} {}", glyphs::PAUSED, operation.label()).magenta().bold()
+        );
+        let prompt = format!("Abort the {} ({})? Its progress is discarded.", operation.description().to_lowercase(), operation.abort_command());
+        if !yes && !confirm(&prompt)? {
+            continue;
+        }
+
+        match abort(&repo.path, operation) {
+            Ok(()) => aborted += 1,
+            Err(e) => eprintln!("   {} {}", glyphs::WARNING, e),
+        }
+    }
+
+    if found == 0 {
+        println!("{} No repositories have an operation in progress", glyphs::DONE);
+    } else {
+        println!("{} Aborted {} of {} operations", glyphs::DONE, aborted, found);
+    }
+    Ok(())
+}
@@ -0,0 +1,64 @@
+use anyhow::Result;
+use std::io::{self, Write};
+use std::process::Stdio;
+use std::thread;
+
+use crate::config::Config;
+use crate::{glyphs, shell, RepoStatus};
+
+/// Run every `[[hooks]]` command from the config once the scan is done. Each
+/// command gets the repositories as a JSON array on stdin (the same document
+/// `--json` prints); its stdout and stderr go to stderr so they never mix
+/// with git-nexus's own output. Failures are reported, not fatal.
+///
+/// Hooks from a `.git-nexus.toml` in the current directory could come from
+/// any cloned repository, so they only run when `trust_local` is set.
+pub fn run_post_scan_hooks(repos: &[RepoStatus], config: &Config, trust_local: bool) -> Result<()> {
+    if config.hooks.is_empty() {
+        return Ok(());
+    }
+    if config.from_current_dir && !trust_local {
+        eprintln!(
+            "{} not running {} post-scan hooks from ./.git-nexus.toml; pass --trust-local-config to allow them",
+            glyphs::WARNING,
+            config.hooks.len()
+        );
+        return Ok(());
+    }
+
+    let report = serde_json::to_string(repos)?;
+
+    for hook in &config.hooks {
+        log::info!("running post-scan hook `{}`", hook.command);
+        let child = shell::command(&hook.command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::from(io::stderr()))
+            .stderr(Stdio::inherit())
+            .spawn();
+        let mut child = match child {
+            Ok(child) => child,
+            Err(e) => {
+                eprintln!("{} post-scan hook `{}` could not start: {}", glyphs::WARNING, hook.command, e);
+                continue;
+            }
+        };
+
+        // Feed stdin from a thread so a hook that ignores its input (or reads
+        // it slowly) cannot deadlock against a full pipe.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let payload = report.clone();
+        let writer = thread::spawn(move || {
+            let _ = stdin.write_all(payload.as_bytes());
+        });
+
+        let status = child.wait()?;
+        let _ = writer.join();
+
+        if !status.success() {
+            let exit = status.code().map_or("a signal".to_string(), |code| format!("exit {}", code));
+            eprintln!("{} post-scan hook `{}` failed ({})", glyphs::WARNING, hook.command, exit);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,18 @@
+use anyhow::{bail, Result};
+use std::io::{self, BufRead, IsTerminal, Write};
+
+/// Ask a yes/no question on stdin; anything but `y`/`yes` means no. Fails
+/// when stdin is not a terminal (CI, pipes), where nobody could answer and
+/// an empty read would quietly count as "no".
+pub fn confirm(prompt: &str) -> Result<bool> {
+    if !io::stdin().is_terminal() {
+        bail!("cannot ask \"{}\": stdin is not a terminal; pass --yes to proceed without prompting", prompt);
+    }
+
+    print!("   {} [y/N] ", prompt);
+    io::stdout().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
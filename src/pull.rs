@@ -0,0 +1,169 @@
+use anyhow::Result;
+use colored::*;
+use rayon::prelude::*;
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::prompt::confirm;
+use crate::{glyphs, RepoStatus};
+
+/// Fast-forward every clean repository that is behind its upstream. The
+/// eligible repositories are listed first, along with the ones that will be
+/// skipped and why, and confirmed once (unless `yes`); then all pulls run in
+/// parallel. With `fail_fast`, pulls that have not started yet are skipped
+/// once one fails, e.g. when credentials are broken for every repository.
+pub fn pull_behind(repos: &[RepoStatus], root: Option<&Path>, yes: bool, fail_fast: bool) -> Result<()> {
+    let mut candidates = Vec::new();
+    let mut up_to_date = 0;
+    for repo in repos {
+        match skip_reason(repo) {
+            None => candidates.push(repo),
+            Some(SkipReason::UpToDate) => up_to_date += 1,
+            Some(reason) => println!(
+                "{} {} {}",
+                glyphs::REPO,
+                repo.display_path(root).bright_black(),
+                format!("skipped: {}", reason.describe(repo)).bright_black()
+            ),
+        }
+    }
+    if up_to_date > 0 {
+        println!("{}", format!("{} repositories already up to date", up_to_date).bright_black());
+    }
+
+    if candidates.is_empty() {
+        println!("{} No clean repositories are behind their upstream", glyphs::DONE);
+        return Ok(());
+    }
+
+    println!();
+    for repo in &candidates {
+        println!(
+            "{} {} ({}) {}",
+            glyphs::REPO,
+            repo.display_path(root).bold(),
+            repo.branch.as_deref().unwrap_or("-").bright_blue(),
+            format!("{}{}", glyphs::BEHIND, repo.behind).red()
+        );
+    }
+
+    if !yes && !confirm(&format!("Pull {} repositories (fast-forward only)?", candidates.len()))? {
+        return Ok(());
+    }
+
+    // Rayon can't cancel queued tasks, so each one checks this first.
+    let stop = AtomicBool::new(false);
+    let results: Vec<_> = candidates
+        .par_iter()
+        .map(|repo| {
+            if stop.load(Ordering::Relaxed) {
+                return (*repo, None);
+            }
+            let result = pull(&repo.path);
+            if fail_fast && result.is_err() {
+                stop.store(true, Ordering::Relaxed);
+            }
+            (*repo, Some(result))
+        })
+        .collect();
+
+    let mut failed = 0;
+    let mut skipped = 0;
+    for (repo, result) in &results {
+        match result {
+            Some(Err(reason)) => {
+                failed += 1;
+                println!("{} {} {}", glyphs::WARNING, repo.display_path(root).bold(), format!("failed: {}", reason).red());
+            }
+            Some(Ok(())) => {}
+            None => skipped += 1,
+        }
+    }
+    let pulled = results.len() - failed - skipped;
+    if skipped > 0 {
+        println!(
+            "{} Pulled {} of {} repositories, {} failed, {} skipped after the first failure (--fail-fast)",
+            glyphs::DONE,
+            pulled,
+            results.len(),
+            failed,
+            skipped
+        );
+    } else {
+        println!("{} Pulled {} of {} repositories, {} failed", glyphs::DONE, pulled, results.len(), failed);
+    }
+    Ok(())
+}
+
+enum SkipReason {
+    Failed,
+    InProgress,
+    Dirty,
+    Detached,
+    NoUpstream,
+    UpToDate,
+}
+
+impl SkipReason {
+    fn describe(&self, repo: &RepoStatus) -> String {
+        match self {
+            Self::Failed => "could not be analyzed".to_string(),
+            Self::InProgress => match repo.repo_state {
+                Some(operation) => format!("{} in progress", operation.description()),
+                None => "operation in progress".to_string(),
+            },
+            Self::Dirty => "uncommitted changes".to_string(),
+            Self::Detached => "detached HEAD".to_string(),
+            Self::NoUpstream => "no upstream branch".to_string(),
+            Self::UpToDate => "up to date".to_string(),
+        }
+    }
+}
+
+/// Why `pull-behind` leaves a repository alone, or `None` if it will pull it.
+fn skip_reason(repo: &RepoStatus) -> Option<SkipReason> {
+    if repo.error.is_some() {
+        Some(SkipReason::Failed)
+    } else if repo.repo_state.is_some() {
+        Some(SkipReason::InProgress)
+    } else if repo.behind > 0 && !repo.is_clean {
+        Some(SkipReason::Dirty)
+    } else if repo.behind > 0 {
+        None
+    } else if repo.is_detached() {
+        Some(SkipReason::Detached)
+    } else if has_upstream(&repo.path) {
+        Some(SkipReason::UpToDate)
+    } else {
+        Some(SkipReason::NoUpstream)
+    }
+}
+
+fn has_upstream(repo_path: &Path) -> bool {
+    let Ok(repo) = git2::Repository::open(repo_path) else {
+        return false;
+    };
+    let Ok(head) = repo.head() else {
+        return false;
+    };
+    // Like get_branch_divergence: the shorthand is not always valid UTF-8.
+    head.is_branch() && git2::Branch::wrap(head).upstream().is_ok()
+}
+
+/// `git pull --ff-only`, through the CLI so the user's credentials and
+/// protocols apply. A branch that has diverged fails instead of merging.
+fn pull(repo_path: &Path) -> Result<(), String> {
+    log::info!("running git pull --ff-only in {}", repo_path.display());
+    let output = Command::new("git")
+        .args(["pull", "--ff-only", "--quiet"])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
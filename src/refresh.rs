@@ -0,0 +1,73 @@
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, SystemTime};
+
+/// At most this many fetches run at once, so a large workspace doesn't open
+/// hundreds of connections to the same host.
+const MAX_CONCURRENT_FETCHES: usize = 8;
+
+/// Fetch every repository whose last fetch is older than `ttl`, before its
+/// ahead/behind counts are computed. The age is the mtime of `FETCH_HEAD`; a
+/// repository that was never fetched counts as stale. Repositories without a
+/// remote are left alone, and failures are only logged: the scan then reports
+/// the counts from the last successful fetch.
+pub fn refresh_stale(git_dirs: &[PathBuf], ttl: Duration) {
+    if crate::offline::enabled() {
+        crate::offline::note_skipped("--refresh-remotes-ttl");
+        return;
+    }
+    let stale: Vec<&PathBuf> = git_dirs.iter().filter(|path| needs_fetch(path, ttl)).collect();
+    if stale.is_empty() {
+        return;
+    }
+    log::info!("fetching {} repositories last fetched more than {:?} ago", stale.len(), ttl);
+
+    let pool = match rayon::ThreadPoolBuilder::new().num_threads(MAX_CONCURRENT_FETCHES).build() {
+        Ok(pool) => pool,
+        Err(e) => {
+            log::warn!("could not start fetch threads: {}", e);
+            return;
+        }
+    };
+    pool.install(|| {
+        stale.par_iter().for_each(|path| {
+            if let Err(reason) = fetch(path) {
+                log::warn!("failed to fetch {}: {}", path.display(), reason);
+            }
+        });
+    });
+}
+
+fn needs_fetch(path: &Path, ttl: Duration) -> bool {
+    let Ok(repo) = git2::Repository::open(path) else {
+        return false;
+    };
+    if repo.remotes().map(|r| r.is_empty()).unwrap_or(true) {
+        return false;
+    }
+    let age = std::fs::metadata(repo.path().join("FETCH_HEAD"))
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+    age.is_none_or(|age| age > ttl)
+}
+
+/// `git fetch` through the CLI, like `pull`, so credentials and protocols
+/// apply. Prompts are turned off: an unattended scan must not hang waiting
+/// for a password.
+fn fetch(repo_path: &Path) -> Result<(), String> {
+    log::debug!("running git fetch in {}", repo_path.display());
+    let output = Command::new("git")
+        .args(["fetch", "--quiet"])
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,199 @@
+use anyhow::{bail, Result};
+use colored::*;
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+use crate::prompt::confirm;
+use crate::{glyphs, RepoStatus};
+
+/// Stands for the repository's directory name in a URL template.
+const NAME_PLACEHOLDER: &str = "{name}";
+
+/// Give every local-only repository a remote built from `url_template`, so
+/// orphaned work can be backed up in one go. The planned URLs are shown and
+/// confirmed once (unless `yes`); with `push`, the current branch is pushed
+/// to the new remote and set as its upstream.
+pub fn add_remotes(
+    repos: &[RepoStatus],
+    root: Option<&Path>,
+    url_template: &str,
+    name: &str,
+    push: bool,
+    yes: bool,
+) -> Result<()> {
+    validate_url(url_template)?;
+    if !git2::Remote::is_valid_name(name) {
+        bail!("\"{}\" is not a valid remote name", name);
+    }
+
+    let candidates: Vec<(&RepoStatus, String)> = repos
+        .iter()
+        .filter(|r| r.error.is_none() && !r.has_remote)
+        .map(|r| (r, remote_url(url_template, &r.path)))
+        .collect();
+
+    if candidates.is_empty() {
+        println!("{} Every repository already has a remote", glyphs::DONE);
+        return Ok(());
+    }
+    if candidates.len() > 1 && !url_template.contains(NAME_PLACEHOLDER) {
+        bail!(
+            "{} local-only repositories would all get the same URL; put {} in it for the directory name",
+            candidates.len(),
+            NAME_PLACEHOLDER
+        );
+    }
+    check_unique_urls(&candidates, root)?;
+
+    for (repo, url) in &candidates {
+        println!("{} {} {} {}", glyphs::REPO, repo.display_path(root).bold(), name.bright_blue(), url);
+    }
+
+    let action = if push { "Add and push to" } else { "Add" };
+    if !yes && !confirm(&format!("{} {} remotes?", action, candidates.len()))? {
+        return Ok(());
+    }
+
+    let mut failed = 0;
+    for (repo, url) in &candidates {
+        let mut result = add_remote(&repo.path, name, url);
+        if push && result.is_ok() {
+            result = push_head(&repo.path, name);
+        }
+        if let Err(reason) = result {
+            failed += 1;
+            println!("{} {} {}", glyphs::WARNING, repo.display_path(root).bold(), format!("failed: {}", reason).red());
+        }
+    }
+    println!("{} Added {} of {} remotes, {} failed", glyphs::DONE, candidates.len() - failed, candidates.len(), failed);
+    Ok(())
+}
+
+/// Repositories in different directories can share a name, and would then
+/// push to the same remote; refuse before anything is added.
+fn check_unique_urls(candidates: &[(&RepoStatus, String)], root: Option<&Path>) -> Result<()> {
+    let mut by_url: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (repo, url) in candidates {
+        by_url.entry(url).or_default().push(repo.display_path(root));
+    }
+    let collisions: Vec<String> = by_url
+        .into_iter()
+        .filter(|(_, repos)| repos.len() > 1)
+        .map(|(url, repos)| format!("{} ({})", url, repos.join(", ")))
+        .collect();
+    if !collisions.is_empty() {
+        bail!(
+            "several repositories would get the same remote URL: {}; rename them or use a template that tells them apart",
+            collisions.join("; ")
+        );
+    }
+    Ok(())
+}
+
+fn remote_url(template: &str, repo_path: &Path) -> String {
+    let dir_name = repo_path
+        .canonicalize()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+    template.replace(NAME_PLACEHOLDER, &dir_name)
+}
+
+/// Accept the URL forms git understands for remotes: `scheme://...` for
+/// https, ssh, git and file, scp-like `[user@]host:path`, and local paths
+/// that are absolute or start with `./` or `../`.
+fn validate_url(url: &str) -> Result<()> {
+    if url.chars().any(char::is_whitespace) {
+        bail!("remote URL \"{}\" contains whitespace", url);
+    }
+    let valid = match url.split_once("://") {
+        Some((scheme, rest)) => {
+            matches!(scheme, "https" | "http" | "ssh" | "git" | "file") && !rest.is_empty()
+        }
+        None if Path::new(url).is_absolute() || url.starts_with("./") || url.starts_with("../") => true,
+        // git reads `host:path` as scp-like only when no `/` comes before the colon.
+        None => url
+            .split_once(':')
+            .is_some_and(|(host, path)| !host.is_empty() && !host.contains('/') && !path.is_empty()),
+    };
+    if !valid {
+        bail!(
+            "\"{}\" is not a remote URL; expected e.g. https://host/{{name}}.git, git@host:{{name}}.git or /srv/backup/{{name}}.git",
+            url
+        );
+    }
+    Ok(())
+}
+
+fn add_remote(repo_path: &Path, name: &str, url: &str) -> Result<(), String> {
+    log::info!("adding remote {} {} to {}", name, url, repo_path.display());
+    let repo = git2::Repository::open(repo_path).map_err(|e| e.message().to_string())?;
+    repo.remote(name, url).map_err(|e| e.message().to_string())?;
+    Ok(())
+}
+
+/// `git push -u`, through the CLI like `pull`, so credentials apply.
+fn push_head(repo_path: &Path, remote: &str) -> Result<(), String> {
+    log::info!("running git push -u {} HEAD in {}", remote, repo_path.display());
+    let output = Command::new("git")
+        .args(["push", "--quiet", "-u", remote, "HEAD"])
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accepts_remote_urls_and_local_paths() {
+        for url in [
+            "https://github.com/me/{name}.git",
+            "ssh://git@host/{name}.git",
+            "file:///srv/git/{name}.git",
+            "git@github.com:me/{name}.git",
+            "backup:repos/{name}.git",
+            "/srv/backup/{name}.git",
+            "./mirror/{name}.git",
+            "../mirror/{name}.git",
+        ] {
+            assert!(validate_url(url).is_ok(), "{} should be accepted", url);
+        }
+    }
+
+    #[test]
+    fn rejects_what_git_would_not_read_as_a_remote() {
+        for url in ["", "ftp://host/{name}", "https://", "host:", "dir/host:path", "my repo", "{name}.git"] {
+            assert!(validate_url(url).is_err(), "{} should be rejected", url);
+        }
+    }
+
+    #[test]
+    fn refuses_repositories_that_would_share_a_url() {
+        let dir = tempfile::tempdir().unwrap();
+        let repos: Vec<RepoStatus> = ["a/app", "b/app", "c/tool"]
+            .iter()
+            .map(|name| {
+                let path = dir.path().join(name);
+                std::fs::create_dir_all(&path).unwrap();
+                let mut repo = RepoStatus::failed(path, String::new());
+                repo.error = None;
+                repo
+            })
+            .collect();
+
+        let err = add_remotes(&repos, Some(dir.path()), "/srv/backup/{name}.git", "origin", false, true).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("/srv/backup/app.git"), "{}", message);
+        assert!(message.contains("a/app") && message.contains("b/app"), "{}", message);
+        assert!(!message.contains("tool"), "{}", message);
+    }
+}
@@ -0,0 +1,125 @@
+use anyhow::Result;
+use chrono::Local;
+use colored::*;
+use git2::Repository;
+use serde::Serialize;
+use std::path::Path;
+
+use crate::prompt::confirm;
+use crate::{glyphs, RepoStatus};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    /// Creation time as a Unix timestamp.
+    pub created: i64,
+}
+
+impl StashEntry {
+    pub fn age_days(&self) -> i64 {
+        (Local::now().timestamp() - self.created).max(0) / 86_400
+    }
+
+    pub fn age(&self) -> String {
+        match self.age_days() {
+            0 => "today".to_string(),
+            1 => "1 day ago".to_string(),
+            days => format!("{} days ago", days),
+        }
+    }
+}
+
+/// List the stashes of a repository, newest (`stash@{0}`) first.
+pub fn list_stashes(repo_path: &Path) -> Result<Vec<StashEntry>, git2::Error> {
+    let mut repo = Repository::open(repo_path)?;
+
+    let mut stashes = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        stashes.push((index, message.to_string(), *oid));
+        true
+    })?;
+
+    Ok(stashes
+        .into_iter()
+        .map(|(index, message, oid)| StashEntry {
+            index,
+            message,
+            created: repo.find_commit(oid).map(|c| c.time().seconds()).unwrap_or(0),
+        })
+        .collect())
+}
+
+/// Drop the given stashes. Indices shift down after every drop, so they are
+/// removed from the highest index to the lowest.
+pub fn drop_stashes(repo_path: &Path, indices: &[usize]) -> Result<(), git2::Error> {
+    let mut repo = Repository::open(repo_path)?;
+
+    let mut indices = indices.to_vec();
+    indices.sort_unstable_by(|a, b| b.cmp(a));
+    indices.dedup();
+    for index in indices {
+        repo.stash_drop(index)?;
+    }
+    Ok(())
+}
+
+/// Offer to drop stashes across all scanned repositories. With `older_than`
+/// only stashes at least that many days old are candidates; without it every
+/// stash is, which clears the repository's stash list. Each repository is
+/// confirmed separately unless `yes` is set.
+pub fn prune_stashes(repos: &[RepoStatus], older_than: Option<i64>, yes: bool) -> Result<()> {
+    let mut dropped = 0;
+    let mut touched = 0;
+
+    for repo in repos.iter().filter(|r| r.error.is_none()) {
+        let stashes = match list_stashes(&repo.path) {
+            Ok(stashes) => stashes,
+            Err(e) => {
+                eprintln!("{} {}: {}", glyphs::WARNING, repo.path.display(), e.message());
+                continue;
+            }
+        };
+        let candidates: Vec<_> = stashes
+            .iter()
+            .filter(|s| older_than.is_none_or(|days| s.age_days() >= days))
+            .collect();
+        if candidates.is_empty() {
+            continue;
+        }
+
+        println!("{} {}", glyphs::REPO, repo.path.display().to_string().bold());
+        for stash in &candidates {
+            println!(
+                "   {} stash@{{{}}} {} {}",
+                glyphs::STASH,
+                stash.index,
+                stash.message,
+                format!("({})", stash.age()).bright_black()
+            );
+        }
+
+        let prompt = if candidates.len() == stashes.len() {
+            format!("Clear all {} stashes?", stashes.len())
+        } else {
+            format!("Drop {} of {} stashes?", candidates.len(), stashes.len())
+        };
+        if !yes && !confirm(&prompt)? {
+            println!();
+            continue;
+        }
+
+        let indices: Vec<_> = candidates.iter().map(|s| s.index).collect();
+        match drop_stashes(&repo.path, &indices) {
+            Ok(()) => {
+                dropped += indices.len();
+                touched += 1;
+            }
+            Err(e) => eprintln!("{} {}: {}", glyphs::WARNING, repo.path.display(), e.message()),
+        }
+        println!();
+    }
+
+    println!("{} Dropped {} stashes in {} repositories", glyphs::DONE, dropped, touched);
+    Ok(())
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use colored::*;
+use git2::{Delta, DiffOptions, FileMode, Repository, SubmoduleIgnore};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{glyphs, RepoStatus};
+
+/// Submodules that need `git submodule update`, by name.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SubmoduleStatus {
+    pub total: usize,
+    /// Listed in `.gitmodules` but never checked out.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub uninitialized: Vec<String>,
+    /// Checked out at a different commit than the superproject records.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub out_of_date: Vec<String>,
+}
+
+impl SubmoduleStatus {
+    /// `None` for repositories without submodules.
+    pub fn detect(repo: &Repository) -> Option<Self> {
+        let submodules = repo.submodules().ok()?;
+        if submodules.is_empty() {
+            return None;
+        }
+
+        let mut status = Self { total: submodules.len(), ..Self::default() };
+        for submodule in &submodules {
+            let Some(name) = submodule.name() else {
+                continue;
+            };
+            let Ok(flags) = repo.submodule_status(name, SubmoduleIgnore::None) else {
+                continue;
+            };
+            if flags.is_wd_uninitialized() {
+                status.uninitialized.push(name.to_string());
+            } else if flags.is_wd_modified() {
+                status.out_of_date.push(name.to_string());
+            }
+        }
+        Some(status)
+    }
+
+    pub fn needs_update(&self) -> bool {
+        !self.uninitialized.is_empty() || !self.out_of_date.is_empty()
+    }
+}
+
+/// A submodule whose recorded commit in the superproject differs between a
+/// past revision and `HEAD`.
+#[derive(Debug, Clone, Serialize)]
+pub struct PointerMove {
+    /// The commit the superproject pointed at; `None` if the submodule was
+    /// added since.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub from: Option<String>,
+    pub to: String,
+}
+
+impl PointerMove {
+    pub fn describe(&self) -> String {
+        match self.from {
+            Some(ref from) => format!("pointer moved {} {} {}", short(from), glyphs::ARROW.get(), short(&self.to)),
+            None => format!("pointer added at {}", short(&self.to)),
+        }
+    }
+}
+
+fn short(oid: &str) -> &str {
+    &oid[..oid.len().min(7)]
+}
+
+/// For `--changed-since`: the submodules of the repository containing `root`
+/// whose recorded commit differs between `rev` and `HEAD`, keyed by their
+/// canonicalized checkout path. Removed submodules have no checkout to
+/// report and are left out.
+pub fn moved_since(root: &Path, rev: &str) -> Result<HashMap<PathBuf, PointerMove>> {
+    let repo = Repository::discover(root)
+        .with_context(|| format!("--changed-since needs {} to be inside a git repository", root.display()))?;
+    let workdir = repo.workdir().context("--changed-since does not work in a bare repository")?;
+    let old_tree = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_tree())
+        .with_context(|| format!("cannot resolve {} to a commit", rev))?;
+    let new_tree = repo.head().and_then(|head| head.peel_to_tree()).context("cannot read HEAD")?;
+
+    // Only gitlinks are of interest, so leave the contents of other files alone.
+    let mut options = DiffOptions::new();
+    options.skip_binary_check(true);
+    let diff = repo.diff_tree_to_tree(Some(&old_tree), Some(&new_tree), Some(&mut options))?;
+
+    let mut moves = HashMap::new();
+    for delta in diff.deltas() {
+        let (old, new) = (delta.old_file(), delta.new_file());
+        if new.mode() != FileMode::Commit || delta.status() == Delta::Deleted {
+            continue;
+        }
+        let Some(path) = new.path() else {
+            continue;
+        };
+        let from = (old.mode() == FileMode::Commit && delta.status() != Delta::Added).then(|| old.id().to_string());
+        let to = new.id().to_string();
+        log::debug!("submodule {} recorded at {} (was {:?})", path.display(), to, from);
+
+        let checkout = workdir.join(path);
+        let key = checkout.canonicalize().unwrap_or(checkout);
+        moves.insert(key, PointerMove { from, to });
+    }
+    Ok(moves)
+}
+
+/// Run `git submodule update --init --recursive` for every submodule that
+/// needs it, one submodule at a time so each result is reported on its own.
+/// This goes through the git CLI because fetching a submodule needs network
+/// transports.
+pub fn update_submodules(repos: &[RepoStatus]) -> Result<()> {
+    let mut updated = 0;
+    let mut failed = 0;
+
+    for repo in repos {
+        let Some(status) = repo.submodules.as_ref().filter(|s| s.needs_update()) else {
+            continue;
+        };
+
+        println!("{} {}", glyphs::REPO, repo.path.display().to_string().bold());
+        for name in status.uninitialized.iter().chain(&status.out_of_date) {
+            match update_one(&repo.path, name) {
+                Ok(()) => {
+                    updated += 1;
+                    println!("   {} {} {}", glyphs::TREE.get().bright_black(), name, "updated".green());
+                }
+                Err(reason) => {
+                    failed += 1;
+                    println!("   {} {} {}", glyphs::TREE.get().bright_black(), name, format!("failed: {}", reason).red());
+                }
+            }
+        }
+    }
+
+    if updated + failed == 0 {
+        println!("{} All submodules are up to date", glyphs::DONE);
+    } else {
+        println!("{} Updated {} submodules, {} failed", glyphs::DONE, updated, failed);
+    }
+    Ok(())
+}
+
+fn update_one(repo_path: &Path, name: &str) -> Result<(), String> {
+    let repo = Repository::open(repo_path).map_err(|e| e.message().to_string())?;
+    let submodule = repo.find_submodule(name).map_err(|e| e.message().to_string())?;
+    let path = submodule.path().to_path_buf();
+    log::info!("updating submodule {} in {}", path.display(), repo_path.display());
+
+    let output = Command::new("git")
+        .args(["submodule", "update", "--init", "--recursive", "--"])
+        .arg(&path)
+        .current_dir(repo_path)
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
@@ -0,0 +1,235 @@
+use serde::Serialize;
+
+use crate::{audit, RepoStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Priority {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Priority {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Low => "LOW",
+            Self::Medium => "MEDIUM",
+            Self::High => "HIGH",
+            Self::Critical => "CRITICAL",
+        }
+    }
+
+    /// Each level outweighs two of the one below, for `issue_score`.
+    fn weight(&self) -> u32 {
+        match self {
+            Self::Low => 1,
+            Self::Medium => 3,
+            Self::High => 7,
+            Self::Critical => 15,
+        }
+    }
+}
+
+/// Something the user should probably do in a repository, with the command
+/// that does it when there is an obvious one.
+#[derive(Debug, Clone, Serialize)]
+pub struct Suggestion {
+    pub priority: Priority,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl Suggestion {
+    pub fn new(priority: Priority, message: impl Into<String>, command: Option<&str>) -> Self {
+        Self {
+            priority,
+            message: message.into(),
+            command: command.map(String::from),
+        }
+    }
+}
+
+/// How much attention a repository needs, for `--sort issues`: the summed
+/// weights of its `suggest` priorities, leaving out the config audit so
+/// sorting doesn't spawn git for every repository. Unanalyzable repositories
+/// count as critical.
+pub fn issue_score(repo: &RepoStatus) -> u32 {
+    if repo.error.is_some() && !repo.incomplete {
+        return Priority::Critical.weight();
+    }
+    status_suggestions(repo).iter().map(|s| s.priority.weight()).sum()
+}
+
+/// Suggestions for one repository, most urgent first. Uncommitted changes
+/// and missing upstream commits are critical on an important branch, and
+/// unpushed commits there are high priority. Git config problems found by
+/// `audit` are included too.
+pub fn suggest(repo: &RepoStatus) -> Vec<Suggestion> {
+    let mut suggestions = status_suggestions(repo);
+    if !repo.incomplete && repo.error.is_none() {
+        suggestions.extend(audit::config_suggestions(&repo.path));
+    }
+    suggestions.sort_by_key(|s| std::cmp::Reverse(s.priority));
+    suggestions
+}
+
+/// The suggestions that follow from the scanned status alone.
+fn status_suggestions(repo: &RepoStatus) -> Vec<Suggestion> {
+    if repo.incomplete {
+        return vec![Suggestion::new(
+            Priority::Critical,
+            "Incomplete .git directory, probably an interrupted clone; re-clone it or check what can be recovered",
+            Some("git fsck"),
+        )];
+    }
+    if repo.error.is_some() {
+        return Vec::new();
+    }
+
+    let mut suggestions = Vec::new();
+
+    if let Some(operation) = repo.repo_state {
+        let command = match operation.continue_command() {
+            Some(resume) => format!("{}, or abort with git-nexus abort", resume),
+            None => "git-nexus abort".to_string(),
+        };
+        suggestions.push(Suggestion::new(
+            Priority::Critical,
+            format!("{} in progress; finish or abort it", operation.description()),
+            Some(&command),
+        ));
+    }
+
+    if let Some(integrity) = &repo.integrity
+        && !integrity.ok
+    {
+        if integrity.missing_objects > 0 {
+            suggestions.push(Suggestion::new(
+                Priority::Critical,
+                format!("{} objects missing; restore them from another clone or a backup", integrity.missing_objects),
+                Some("git fsck --full"),
+            ));
+        } else {
+            suggestions.push(Suggestion::new(
+                Priority::High,
+                "git fsck reported problems; inspect them before relying on this clone",
+                Some("git fsck --full"),
+            ));
+        }
+    }
+
+    let (dirty, behind, ahead) = if repo.important {
+        (Priority::Critical, Priority::Critical, Priority::High)
+    } else {
+        (Priority::Medium, Priority::High, Priority::Medium)
+    };
+
+    if repo.behind > 0 && !repo.is_clean {
+        suggestions.push(Suggestion::new(
+            behind,
+            format!("{} commits behind with uncommitted changes; stash or commit before pulling", repo.behind),
+            Some("git stash && git pull && git stash pop"),
+        ));
+    } else if repo.behind > 0 {
+        suggestions.push(Suggestion::new(
+            behind,
+            format!("{} commits behind upstream", repo.behind),
+            Some("git pull"),
+        ));
+    } else if !repo.is_clean && repo.repo_state.is_none() && !repo.protected {
+        suggestions.push(Suggestion::new(
+            dirty,
+            "Uncommitted changes; commit or stash them",
+            Some("git add -A && git commit"),
+        ));
+    }
+
+    if repo.ahead > 0 && !repo.protected {
+        suggestions.push(Suggestion::new(
+            ahead,
+            format!("{} unpushed commits", repo.ahead),
+            Some("git push"),
+        ));
+    }
+
+    // On a protected branch, committing or pushing is the wrong advice: the
+    // work should move to a feature branch first.
+    if repo.protected && repo.repo_state.is_none() {
+        let branch = repo.branch.as_deref().unwrap_or("HEAD");
+        if repo.ahead > 0 {
+            suggestions.push(Suggestion::new(
+                Priority::Critical,
+                format!("{} commits made directly on protected branch {}; move them to a feature branch", repo.ahead, branch),
+                Some("git branch <feature-branch> && git reset --keep @{u} && git switch <feature-branch>"),
+            ));
+        } else if !repo.is_clean {
+            suggestions.push(Suggestion::new(
+                Priority::High,
+                format!("Uncommitted changes on protected branch {}; continue on a feature branch", branch),
+                Some("git switch -c <feature-branch>"),
+            ));
+        }
+    }
+
+    if let Some(submodules) = repo.submodules.as_ref().filter(|s| s.needs_update()) {
+        let mut problems = Vec::new();
+        if !submodules.uninitialized.is_empty() {
+            problems.push(format!("{} uninitialized", submodules.uninitialized.len()));
+        }
+        if !submodules.out_of_date.is_empty() {
+            problems.push(format!("{} out of date", submodules.out_of_date.len()));
+        }
+        suggestions.push(Suggestion::new(
+            Priority::Medium,
+            format!("Submodules need updating: {}", problems.join(", ")),
+            Some("git-nexus update-submodules"),
+        ));
+    }
+
+    if repo.unborn_branch {
+        suggestions.push(Suggestion::new(
+            Priority::Low,
+            "HEAD is on a branch with no commits while other branches exist; commit to start it, or switch back if the branch was deleted",
+            Some("git branch"),
+        ));
+    }
+
+    if repo.is_detached() && repo.repo_state.is_none() {
+        suggestions.push(Suggestion::new(
+            Priority::Low,
+            "HEAD is detached; switch back to a branch",
+            Some("git switch -"),
+        ));
+    }
+
+    if !repo.has_remote {
+        suggestions.push(Suggestion::new(
+            Priority::Medium,
+            "No remote configured; this work exists only on this machine, push it somewhere for backup",
+            Some("git remote add origin <url> && git push -u origin HEAD"),
+        ));
+    }
+
+    if repo.is_shallow {
+        suggestions.push(Suggestion::new(
+            Priority::Low,
+            "Shallow clone; ahead/behind counts and history may be incomplete",
+            Some("git fetch --unshallow"),
+        ));
+    }
+
+    if let Some(stashes) = repo.stash_count
+        && stashes > 0
+    {
+        suggestions.push(Suggestion::new(
+            Priority::Low,
+            format!("{} stashes; review and drop the ones you no longer need", stashes),
+            Some("git-nexus prune-stashes"),
+        ));
+    }
+
+    suggestions
+}
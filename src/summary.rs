@@ -0,0 +1,120 @@
+use colored::*;
+use serde::Serialize;
+
+use crate::config::DisplayConfig;
+use crate::{glyphs, RepoStatus};
+
+/// Counts of repositories that need attention, by reason. A repository can
+/// count towards several reasons but only once towards `needs_attention`.
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct IssueSummary {
+    pub total: usize,
+    pub needs_attention: usize,
+    pub dirty: usize,
+    pub ahead: usize,
+    pub behind: usize,
+    pub detached: usize,
+    /// With stashes; always zero without verbose data.
+    pub stashed: usize,
+    /// Failed `--verify`; always zero without it.
+    pub corrupt: usize,
+}
+
+impl IssueSummary {
+    pub fn from_repos(repos: &[RepoStatus]) -> Self {
+        let mut summary = Self { total: repos.len(), ..Self::default() };
+
+        for repo in repos.iter().filter(|r| r.error.is_none()) {
+            summary.dirty += !repo.is_clean as usize;
+            summary.ahead += (repo.ahead > 0) as usize;
+            summary.behind += (repo.behind > 0) as usize;
+            summary.detached += repo.is_detached() as usize;
+            summary.stashed += repo.stash_count.is_some_and(|s| s > 0) as usize;
+            summary.corrupt += repo.integrity.as_ref().is_some_and(|i| !i.ok) as usize;
+            summary.needs_attention += needs_attention(repo) as usize;
+        }
+
+        summary
+    }
+
+    /// The one-line headline shown above the repository listing, colored by
+    /// the `attention_warn` / `attention_alert` thresholds.
+    pub fn banner(&self, display: &DisplayConfig) -> String {
+        if self.needs_attention == 0 {
+            return format!("{} All {} repos are in good shape", glyphs::FOUND, self.total)
+                .green()
+                .bold()
+                .to_string();
+        }
+
+        let mut extra = String::new();
+        if self.stashed > 0 {
+            extra.push_str(&format!(", {} with stashes", self.stashed));
+        }
+        if self.corrupt > 0 {
+            extra.push_str(&format!(", {} failed fsck", self.corrupt));
+        }
+        let headline = format!(
+            "{} {} of {} repos need attention ({} dirty, {} ahead, {} behind, {} detached{})",
+            glyphs::WARNING,
+            self.needs_attention,
+            self.total,
+            self.dirty,
+            self.ahead,
+            self.behind,
+            self.detached,
+            extra
+        );
+
+        if self.needs_attention > display.attention_alert {
+            headline.red().bold().to_string()
+        } else if self.needs_attention > display.attention_warn {
+            headline.yellow().bold().to_string()
+        } else {
+            headline.green().bold().to_string()
+        }
+    }
+}
+
+/// Whether a repository has anything to look at: pending work (local
+/// changes, commits to push, stashes), commits to pull, a detached HEAD,
+/// failed checks, or it could not be scanned.
+pub fn needs_attention(repo: &RepoStatus) -> bool {
+    repo.error.is_some()
+        || repo.has_pending_work()
+        || repo.is_detached()
+        || repo.unborn_branch
+        || repo.integrity.as_ref().is_some_and(|i| !i.ok)
+        || repo.behind > 0
+}
+
+/// The reasons `needs_attention` holds, in a few words each.
+pub fn attention_reasons(repo: &RepoStatus) -> Vec<String> {
+    if repo.error.is_some() {
+        return vec!["could not be scanned".to_string()];
+    }
+
+    let mut reasons = Vec::new();
+    if !repo.is_clean {
+        reasons.push("dirty".to_string());
+    }
+    if repo.ahead > 0 {
+        reasons.push(format!("{} ahead", repo.ahead));
+    }
+    if repo.behind > 0 {
+        reasons.push(format!("{} behind", repo.behind));
+    }
+    if let Some(stashes) = repo.stash_count.filter(|&s| s > 0) {
+        reasons.push(format!("{} stashes", stashes));
+    }
+    if repo.is_detached() {
+        reasons.push("detached".to_string());
+    }
+    if repo.unborn_branch {
+        reasons.push("unborn branch".to_string());
+    }
+    if repo.integrity.as_ref().is_some_and(|i| !i.ok) {
+        reasons.push("failed fsck".to_string());
+    }
+    reasons
+}
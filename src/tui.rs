@@ -14,7 +14,7 @@ use ratatui::{
 };
 use std::io;
 
-use crate::RepoStatus;
+use crate::{glyphs, RepoStatus};
 
 pub fn run_tui(repos: Vec<RepoStatus>) -> Result<()> {
     // Setup terminal
@@ -32,15 +32,11 @@ pub fn run_tui(repos: Vec<RepoStatus>) -> Result<()> {
         if let Event::Key(key) = event::read()? {
             match key.code {
                 KeyCode::Char('q') | KeyCode::Esc => break,
-                KeyCode::Down | KeyCode::Char('j') => {
-                    if selected < repos.len().saturating_sub(1) {
-                        selected += 1;
-                    }
+                KeyCode::Down | KeyCode::Char('j') if selected < repos.len().saturating_sub(1) => {
+                    selected += 1;
                 }
                 KeyCode::Up | KeyCode::Char('k') => {
-                    if selected > 0 {
-                        selected -= 1;
-                    }
+                    selected = selected.saturating_sub(1);
                 }
                 KeyCode::Home => selected = 0,
                 KeyCode::End => selected = repos.len().saturating_sub(1),
@@ -89,16 +85,18 @@ fn ui(f: &mut Frame, repos: &[RepoStatus], selected: usize) {
         .iter()
         .enumerate()
         .map(|(i, repo)| {
-            let status_color = if repo.is_clean {
+            let status_color = if repo.error.is_some() {
+                Color::Yellow
+            } else if repo.is_clean {
                 Color::Green
             } else {
                 Color::Red
             };
 
-            let status_text = if repo.is_clean { "CLEAN" } else { "DIRTY" };
+            let status_text = repo.status_label();
 
             let mut spans = vec![
-                Span::styled("📁 ", Style::default()),
+                Span::styled(format!("{} ", glyphs::REPO), Style::default()),
                 Span::styled(
                     repo.path.display().to_string(),
                     Style::default().add_modifier(Modifier::BOLD),
@@ -122,7 +120,7 @@ fn ui(f: &mut Frame, repos: &[RepoStatus], selected: usize) {
             if repo.ahead > 0 {
                 spans.push(Span::raw(" "));
                 spans.push(Span::styled(
-                    format!("↑{}", repo.ahead),
+                    format!("{}{}", glyphs::AHEAD, repo.ahead),
                     Style::default().fg(Color::Yellow),
                 ));
             }
@@ -130,7 +128,7 @@ fn ui(f: &mut Frame, repos: &[RepoStatus], selected: usize) {
             if repo.behind > 0 {
                 spans.push(Span::raw(" "));
                 spans.push(Span::styled(
-                    format!("↓{}", repo.behind),
+                    format!("{}{}", glyphs::BEHIND, repo.behind),
                     Style::default().fg(Color::Red),
                 ));
             }
@@ -151,7 +149,11 @@ fn ui(f: &mut Frame, repos: &[RepoStatus], selected: usize) {
     f.render_widget(list, chunks[1]);
 
     // Footer with help
-    let help = Paragraph::new("↑/k: Up | ↓/j: Down | Home/End: Jump | q/Esc: Quit")
+    let help = Paragraph::new(format!(
+        "{}/k: Up | {}/j: Down | Home/End: Jump | q/Esc: Quit",
+        glyphs::AHEAD,
+        glyphs::BEHIND
+    ))
         .block(Block::default().borders(Borders::ALL).title("Help"))
         .style(Style::default().fg(Color::Gray));
     f.render_widget(help, chunks[2]);
@@ -0,0 +1,80 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use serde::Deserialize;
+
+use crate::cache::UpdateCheck;
+use crate::{glyphs, offline};
+
+const LATEST_RELEASE_URL: &str = "https://api.github.com/repos/Tech-Vexy/git-nexus/releases/latest";
+
+/// Look the latest release up at most once a day.
+const CHECK_TTL: u64 = 24 * 60 * 60;
+
+#[derive(Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Compare this build's version with the latest GitHub release and say
+/// whether an update is available. Nothing is downloaded or installed.
+/// `refresh` skips the day-long cache. Offline, an older cached answer is
+/// used if there is one.
+pub fn check_update(refresh: bool) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let cached = UpdateCheck::load();
+    let latest = match cached.fresh(CHECK_TTL).filter(|_| !refresh) {
+        Some(latest) => {
+            log::debug!("using cached latest release {}", latest);
+            latest.to_string()
+        }
+        None if offline::enabled() => match cached.latest.clone() {
+            Some(latest) => {
+                eprintln!("note: offline mode, comparing with the last known release");
+                latest
+            }
+            None => return offline::ensure_online("check-update"),
+        },
+        None => {
+            let latest = fetch_latest()?;
+            if let Err(e) = UpdateCheck::record(latest.clone()).save() {
+                eprintln!("{} Could not write update cache: {}", glyphs::WARNING, e);
+            }
+            latest
+        }
+    };
+
+    match (parse_version(current), parse_version(&latest)) {
+        (Some(current_version), Some(latest_version)) if latest_version > current_version => {
+            println!(
+                "{} git-nexus {} is available (you have {})",
+                glyphs::WARNING,
+                latest.yellow().bold(),
+                current
+            );
+        }
+        (Some(_), Some(_)) => println!("{} git-nexus {} is up to date", glyphs::DONE, current),
+        _ => println!("{} Installed {}, latest release {}", glyphs::WARNING, current, latest),
+    }
+    Ok(())
+}
+
+fn fetch_latest() -> Result<String> {
+    log::info!("GET {}", LATEST_RELEASE_URL);
+    let response = reqwest::blocking::Client::new()
+        .get(LATEST_RELEASE_URL)
+        .header("User-Agent", "git-nexus")
+        .send()
+        .context("could not reach GitHub to check for updates")?;
+    if !response.status().is_success() {
+        bail!("checking for updates failed: HTTP {}", response.status());
+    }
+    let release: Release = response.json().context("unexpected response from GitHub releases")?;
+    Ok(release.tag_name.trim_start_matches('v').to_string())
+}
+
+/// `1.2.3` as comparable numbers; pre-release suffixes (`1.2.3-rc1`) are
+/// ignored.
+fn parse_version(version: &str) -> Option<Vec<u64>> {
+    let core = version.split(['-', '+']).next()?;
+    core.split('.').map(|part| part.parse().ok()).collect()
+}
@@ -1,61 +1,306 @@
 use anyhow::Result;
-use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::PathBuf;
-use std::sync::mpsc::channel;
+use colored::*;
+use crossterm::{cursor::MoveTo, execute, terminal::{Clear, ClearType}};
+use notify::{Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeSet, VecDeque};
+use std::io::stdout;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
 use std::time::Duration;
 
-use crate::{scan_repositories, display_repo_status, Config};
+use crate::{glyphs, shell, scan_repositories, display_repo_status, RepoStatus, ScanOptions};
 
-pub fn watch_mode(path: &PathBuf, config: &Config, verbose: bool) -> Result<()> {
-    println!("👁️  Watch mode activated. Monitoring for git changes...");
-    println!("   Press Ctrl+C to exit\n");
+/// Lines kept in the dashboard's event log.
+const EVENT_LOG_LEN: usize = 8;
+
+/// Quiet period that ends a burst of file events.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// How watch mode learns about changes.
+#[derive(Debug, Clone, Copy)]
+pub struct Backend {
+    /// Skip OS notifications and poll (`--poll`).
+    pub poll: bool,
+    /// Time between polls, forced or as a fallback.
+    pub poll_interval: Duration,
+}
+
+type EventSender = Sender<Result<Event, notify::Error>>;
+
+pub fn watch_mode(
+    path: &PathBuf,
+    options: &ScanOptions,
+    dashboard: bool,
+    on_change: Option<&str>,
+    backend: Backend,
+) -> Result<()> {
+    if !dashboard {
+        println!("{} Watch mode activated. Monitoring for git changes...", glyphs::WATCH);
+        println!("   Press Ctrl+C to exit\n");
+    }
 
     let (tx, rx) = channel();
-    
-    let mut watcher = RecommendedWatcher::new(
-        move |res: Result<Event, notify::Error>| {
-            let _ = tx.send(res);
-        },
-        notify::Config::default().with_poll_interval(Duration::from_secs(2)),
-    )?;
-    
-    watcher.watch(path, RecursiveMode::Recursive)?;
+    // Kept alive for as long as events are read.
+    let _watcher = start_watcher(path, tx, backend)?;
+
+    let mut events = VecDeque::with_capacity(EVENT_LOG_LEN);
 
     // Initial scan
-    print_scan(path, config, verbose);
+    if dashboard {
+        log_event(&mut events, "watching for git changes".to_string());
+        draw_dashboard(path, options, &events)?;
+    } else {
+        print_scan(path, options);
+    }
 
     loop {
-        match rx.recv() {
-            Ok(Ok(event)) => {
+        let first = match rx.recv() {
+            Ok(res) => res,
+            Err(e) => {
+                eprintln!("Channel error: {}", e);
+                break;
+            }
+        };
+
+        // A single commit touches many files under .git; collect the whole
+        // burst so it costs one rescan and one command run per repository.
+        let mut changed = BTreeSet::new();
+        let mut errors = Vec::new();
+        let mut next = Some(first);
+        while let Some(res) = next.take() {
+            match res {
                 // Check if it's a git-related change
-                let path_str = format!("{:?}", event);
-                if path_str.contains(".git") {
-                    println!("\n🔄 Git change detected, rescanning...\n");
-                    print_scan(path, config, verbose);
-                }
+                Ok(event) => changed.extend(changed_repo(&event)),
+                Err(e) => errors.push(e),
+            }
+            match rx.recv_timeout(DEBOUNCE) {
+                Ok(res) => next = Some(res),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Ok(()),
             }
-            Ok(Err(e)) => {
+        }
+
+        for e in &errors {
+            if dashboard {
+                log_event(&mut events, format!("watch error: {}", e));
+            } else {
                 eprintln!("Watch error: {}", e);
             }
-            Err(e) => {
-                eprintln!("Channel error: {}", e);
-                break;
+        }
+        if changed.is_empty() {
+            if dashboard && !errors.is_empty() {
+                draw_dashboard(path, options, &events)?;
             }
+            continue;
+        }
+
+        if dashboard {
+            for repo in &changed {
+                log_event(&mut events, format!("change in {}", relative_display(path, repo)));
+            }
+        } else {
+            println!("\n{} Git change detected, rescanning...\n", glyphs::RESCAN);
+            print_scan(path, options);
+        }
+
+        if let Some(command) = on_change {
+            for repo in &changed {
+                let summary = run_on_change(command, repo, !dashboard);
+                if dashboard {
+                    log_event(&mut events, format!("{}: {}", relative_display(path, repo), summary));
+                }
+            }
+        }
+
+        if dashboard {
+            draw_dashboard(path, options, &events)?;
         }
     }
 
     Ok(())
 }
 
-fn print_scan(path: &PathBuf, config: &Config, verbose: bool) {
-    let repos = scan_repositories(path, config.scan_depth, verbose, &config.ignore_dirs, false);
+/// Watch `path` with OS notifications, or by polling when asked to or when
+/// notifications can't be set up (e.g. the inotify watch limit is reached).
+/// Network filesystems accept notification watches but never deliver remote
+/// changes, which is why `--poll` exists at all.
+fn start_watcher(path: &Path, tx: EventSender, backend: Backend) -> Result<Box<dyn Watcher>> {
+    let config = notify::Config::default().with_poll_interval(backend.poll_interval);
+    let handler = |tx: EventSender| move |res: Result<Event, notify::Error>| {
+        let _ = tx.send(res);
+    };
+
+    if !backend.poll {
+        let native = RecommendedWatcher::new(handler(tx.clone()), config).and_then(|mut watcher| {
+            watcher.watch(path, RecursiveMode::Recursive)?;
+            Ok(watcher)
+        });
+        match native {
+            Ok(watcher) => return Ok(Box::new(watcher)),
+            Err(e) => eprintln!(
+                "{} File notifications unavailable ({}); polling every {}s instead",
+                glyphs::WARNING,
+                e,
+                backend.poll_interval.as_secs()
+            ),
+        }
+    }
+
+    log::info!("polling {} every {:?}", path.display(), backend.poll_interval);
+    let mut watcher = PollWatcher::new(handler(tx), config)?;
+    watcher.watch(path, RecursiveMode::Recursive)?;
+    Ok(Box::new(watcher))
+}
+
+/// The repository whose `.git` directory `event` touched. Access events are
+/// skipped: the rescan itself opens files under `.git` and would otherwise
+/// trigger another rescan.
+fn changed_repo(event: &Event) -> Option<PathBuf> {
+    if event.kind.is_access() {
+        return None;
+    }
+    event.paths.iter().find_map(|p| {
+        p.ancestors()
+            .find(|a| a.file_name().is_some_and(|name| name == ".git"))
+            .and_then(|git_dir| git_dir.parent())
+            .map(PathBuf::from)
+    })
+}
+
+fn relative_display(root: &Path, repo: &Path) -> String {
+    match repo.strip_prefix(root) {
+        Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+        Ok(relative) => relative.display().to_string(),
+        Err(_) => repo.display().to_string(),
+    }
+}
+
+/// Run the `--on-change` command for one repository through the shell, with
+/// `{repo}` replaced by its quoted path, and describe how it exited. Output
+/// is printed unless the dashboard owns the screen.
+fn run_on_change(command: &str, repo: &Path, show_output: bool) -> String {
+    let command = command.replace("{repo}", &shell::quote(&repo.to_string_lossy()));
+    log::info!("running on-change command `{}` in {}", command, repo.display());
+    let output = shell::command(&command).current_dir(repo).output();
+
+    let summary = match &output {
+        Ok(output) => match output.status.code() {
+            Some(0) => "command succeeded".to_string(),
+            Some(code) => format!("command failed (exit {})", code),
+            None => "command terminated by signal".to_string(),
+        },
+        Err(e) => format!("command could not start: {}", e),
+    };
+
+    if show_output {
+        println!("{} {}", glyphs::RUN, command.bright_black());
+        if let Ok(output) = &output {
+            print!("{}", String::from_utf8_lossy(&output.stdout));
+            eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        }
+        let colored_summary = if output.as_ref().is_ok_and(|o| o.status.success()) {
+            summary.green()
+        } else {
+            summary.red()
+        };
+        println!("{} {}", glyphs::TREE.get().bright_black(), colored_summary);
+    }
+
+    summary
+}
+
+fn print_scan(path: &PathBuf, options: &ScanOptions) {
+    let repos = scan_repositories(path, options).repos;
     
-    println!("🔍 Scan complete at {}", chrono::Local::now().format("%H:%M:%S"));
-    println!("✓ {} repositories found\n", repos.len());
+    println!("{} Scan complete at {}", glyphs::SEARCH, chrono::Local::now().format("%H:%M:%S"));
+    println!("{} {} repositories found\n", glyphs::FOUND, repos.len());
     
     for repo in repos {
-        display_repo_status(&repo, verbose, false);
+        display_repo_status(&repo, None, options.verbosity, options.show_hooks, None, true);
     }
     
     println!("\n---");
 }
+
+fn log_event(events: &mut VecDeque<String>, message: String) {
+    if events.len() == EVENT_LOG_LEN {
+        events.pop_front();
+    }
+    events.push_back(format!("{}  {}", chrono::Local::now().format("%H:%M:%S"), message));
+}
+
+/// Rescan and redraw the whole screen: a header, one compact row per
+/// repository and the most recent watch events.
+fn draw_dashboard(path: &PathBuf, options: &ScanOptions, events: &VecDeque<String>) -> Result<()> {
+    // The table always shows change counts, which are only collected verbosely.
+    let options = ScanOptions { verbosity: options.verbosity.max(crate::VERBOSE_DETAILS), ..*options };
+    let mut repos = scan_repositories(path, &options).repos;
+    repos.sort_by(|a, b| a.path.cmp(&b.path));
+
+    execute!(stdout(), Clear(ClearType::All), MoveTo(0, 0))?;
+
+    let dirty = repos.iter().filter(|r| r.error.is_none() && !r.is_clean).count();
+    println!(
+        "{} {}  {}  {} repositories, {} dirty  {} updated {}",
+        glyphs::WATCH,
+        "git-nexus watch".bold(),
+        path.display(),
+        repos.len(),
+        dirty,
+        glyphs::SEPARATOR,
+        chrono::Local::now().format("%H:%M:%S")
+    );
+    println!("   Press Ctrl+C to exit\n");
+
+    println!(
+        "{:<6} {:<24} {:<10} {:<8} {}",
+        "STATUS".bold(),
+        "BRANCH".bold(),
+        "SYNC".bold(),
+        "CHANGES".bold(),
+        "PATH".bold()
+    );
+    for repo in &repos {
+        println!("{}", dashboard_row(path, repo));
+    }
+
+    println!("\n{}", "Events".bold());
+    for event in events {
+        println!("  {}", event.dimmed());
+    }
+
+    Ok(())
+}
+
+fn dashboard_row(root: &PathBuf, repo: &RepoStatus) -> String {
+    let status = match repo.status_label() {
+        "ERROR" => format!("{:<6}", "ERROR").yellow(),
+        "CLEAN" => format!("{:<6}", "CLEAN").green(),
+        label => format!("{:<6}", label).red(),
+    };
+
+    let relative = repo.path.strip_prefix(root).unwrap_or(&repo.path);
+    let relative = if relative.as_os_str().is_empty() {
+        ".".to_string()
+    } else {
+        relative.display().to_string()
+    };
+
+    if let Some(error) = &repo.error {
+        return format!("{} {:<24} {:<10} {:<8} {} {}", status, "-", "-", "-", relative, error.dimmed());
+    }
+
+    let sync = match (repo.ahead, repo.behind) {
+        (0, 0) => "=".to_string(),
+        (ahead, 0) => format!("{}{}", glyphs::AHEAD, ahead),
+        (0, behind) => format!("{}{}", glyphs::BEHIND, behind),
+        (ahead, behind) => format!("{}{}{}{}", glyphs::AHEAD, ahead, glyphs::BEHIND, behind),
+    };
+    let changes = match (repo.modified_count, repo.untracked_count) {
+        (Some(modified), Some(untracked)) => format!("{}M {}U", modified, untracked),
+        _ => "-".to_string(),
+    };
+    let branch = repo.branch.as_deref().unwrap_or("-");
+
+    format!("{} {:<24} {:<10} {:<8} {}", status, branch, sync, changes, relative)
+}
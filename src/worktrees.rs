@@ -0,0 +1,125 @@
+use anyhow::Result;
+use colored::*;
+use git2::{Repository, WorktreeLockStatus};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use crate::prompt::confirm;
+use crate::{glyphs, RepoStatus};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WorktreeInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub branch: Option<String>,
+    pub locked: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lock_reason: Option<String>,
+    /// The worktree's directory is gone, so git can prune its metadata.
+    pub prunable: bool,
+}
+
+#[derive(Serialize)]
+struct RepoWorktrees<'a> {
+    repo: &'a Path,
+    worktrees: Vec<WorktreeInfo>,
+}
+
+/// Linked worktrees of a repository (the main working tree is not included).
+pub fn list_worktrees(repo_path: &Path) -> Result<Vec<WorktreeInfo>, git2::Error> {
+    let repo = Repository::open(repo_path)?;
+
+    let mut worktrees = Vec::new();
+    for name in repo.worktrees()?.iter().flatten() {
+        let worktree = repo.find_worktree(name)?;
+        let (locked, lock_reason) = match worktree.is_locked() {
+            Ok(WorktreeLockStatus::Locked(reason)) => {
+                (true, reason.map(|r| r.trim().to_string()).filter(|r| !r.is_empty()))
+            }
+            _ => (false, None),
+        };
+        let branch = Repository::open_from_worktree(&worktree)
+            .ok()
+            .and_then(|wt_repo| wt_repo.head().ok().and_then(|h| h.shorthand().map(String::from)));
+
+        worktrees.push(WorktreeInfo {
+            name: name.to_string(),
+            path: worktree.path().to_path_buf(),
+            branch,
+            locked,
+            lock_reason,
+            prunable: worktree.is_prunable(None).unwrap_or(false),
+        });
+    }
+    worktrees.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(worktrees)
+}
+
+/// List the linked worktrees of every scanned repository, grouped under it.
+/// With `prune`, offer to remove the metadata of worktrees whose directory
+/// no longer exists.
+pub fn show_worktrees(repos: &[RepoStatus], root: Option<&Path>, json: bool, prune: bool, yes: bool) -> Result<()> {
+    let mut found = Vec::new();
+    for repo in repos.iter().filter(|r| r.error.is_none()) {
+        match list_worktrees(&repo.path) {
+            Ok(worktrees) if !worktrees.is_empty() => found.push((repo, worktrees)),
+            Ok(_) => {}
+            Err(e) => eprintln!("{} {}: {}", glyphs::WARNING, repo.path.display(), e.message()),
+        }
+    }
+
+    if json {
+        let report: Vec<_> = found
+            .iter()
+            .map(|(repo, worktrees)| RepoWorktrees { repo: &repo.path, worktrees: worktrees.clone() })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    if found.is_empty() {
+        println!("{}", "No linked worktrees found.".yellow());
+        return Ok(());
+    }
+
+    let mut pruned = 0;
+    for (repo, worktrees) in &found {
+        println!("{} {}", glyphs::REPO, repo.display_path(root).bright_white().bold());
+        for worktree in worktrees {
+            let mut line = format!(
+                "   {} {} {}",
+                glyphs::TREE.get().bright_black(),
+                worktree.path.display(),
+                format!("({})", worktree.branch.as_deref().unwrap_or("no branch")).bright_blue()
+            );
+            if worktree.locked {
+                let reason = worktree.lock_reason.as_deref().map(|r| format!(": {}", r)).unwrap_or_default();
+                line.push_str(&format!(" {}", format!("[locked{}]", reason).yellow()));
+            }
+            if worktree.prunable {
+                line.push_str(&format!(" {}", "[prunable]".red()));
+            }
+            println!("{}", line);
+
+            if prune && worktree.prunable {
+                let prompt = format!("Prune stale worktree '{}'?", worktree.name);
+                if yes || confirm(&prompt)? {
+                    match prune_worktree(&repo.path, &worktree.name) {
+                        Ok(()) => pruned += 1,
+                        Err(e) => eprintln!("   {} {}", glyphs::WARNING, e.message()),
+                    }
+                }
+            }
+        }
+    }
+
+    if prune {
+        println!("\n{} Pruned {} worktrees", glyphs::DONE, pruned);
+    }
+    Ok(())
+}
+
+fn prune_worktree(repo_path: &Path, name: &str) -> Result<(), git2::Error> {
+    let repo = Repository::open(repo_path)?;
+    repo.find_worktree(name)?.prune(None)
+}